@@ -0,0 +1,794 @@
+//! Command-line client for the multisig program.
+//!
+//! ```text
+//! p-multisig-cli [--url URL] [--keypair PATH] <COMMAND> [OPTIONS]
+//! ```
+//!
+//! Run without a command to print the list of commands and their options.
+
+mod rpc;
+
+use std::{env, process, str::FromStr};
+
+use bytemuck::Zeroable;
+use p_multisig::{
+    constants::{
+        MAX_ASSERTIONS, MAX_EXECUTORS, MAX_FILTERED_PROGRAMS, MAX_OWNERS, MAX_SIGNING_VAULTS,
+        MAX_THRESHOLD_POLICIES, MULTISIG_SEED, TRANSACTION_SEED,
+    },
+    instruction::{InlineTransactionHeader, MultisigInstruction},
+    state::{
+        AccountState, AssertionKind, ExecutePolicy, Multisig, MultisigHeader, ProgramFilter,
+        ThresholdPolicy, Transaction, TransactionAccount, TransactionAssertion,
+        TransactionAssertions, TransactionHeader, TransactionSigner, TransactionStatus,
+        TransactionSummary, TransactionVaults,
+    },
+};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signer},
+    system_program,
+};
+
+use rpc::{Result, RpcClient};
+
+const PROGRAM: Pubkey = Pubkey::new_from_array(p_multisig::ID);
+
+const DEFAULT_URL: &str = "http://127.0.0.1:8899";
+
+const USAGE: &str = "\
+Usage: p-multisig-cli [--url URL] [--keypair PATH] <COMMAND> [OPTIONS]
+
+Global options:
+  --url URL             RPC endpoint (default: http://127.0.0.1:8899)
+  --keypair PATH        Signer keypair file (default: ~/.config/solana/id.json)
+
+Commands:
+  create-multisig --seed N --threshold N --owner PUBKEY...
+                  [--pause-threshold N] [--guardian PUBKEY]
+                  [--execute-policy any-member|executors|permissionless]
+                  [--executor PUBKEY...]
+                  [--allow-program PUBKEY... | --deny-program PUBKEY...]
+  propose         --multisig PUBKEY --program PUBKEY
+                  [--account PUBKEY[:s][:w]...] [--data HEX] [--vault INDEX...]
+                  [--assert before|after:KIND:ACCOUNT:VALUE...]
+  draft           --multisig PUBKEY --program PUBKEY [same options as propose]
+  update          --transaction PUBKEY --program PUBKEY [same options as propose]
+  activate        --transaction PUBKEY
+  cancel          --transaction PUBKEY
+  approve         --transaction PUBKEY
+  reject          --transaction PUBKEY
+  revoke          --transaction PUBKEY
+  execute         --transaction PUBKEY
+  list            --multisig PUBKEY
+  show-multisig   PUBKEY
+  show-transaction PUBKEY
+
+The keypair is always added as an owner by `create-multisig`. Accounts of
+`propose` are suffixed with `:s` if they must sign and `:w` if writable.
+Assertions refer to an account by its position in the `--account` list; KIND
+is lamports-min, lamports-max, tokens-min, tokens-max (VALUE is an amount) or
+data-hash (VALUE is the hex SHA-256 hash of the account data).
+Drafts can be updated by their proposer until they are activated. Drafts and
+active transactions can be cancelled by their proposer.";
+
+/// Parsed command line: a command, its positional arguments and its options.
+struct Args {
+    command: String,
+    positional: Vec<String>,
+    options: Vec<(String, String)>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self> {
+        let mut positional = Vec::new();
+        let mut options = Vec::new();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("missing value for --{}", name))?;
+                    options.push((name.to_string(), value));
+                }
+                None => positional.push(arg),
+            }
+        }
+        if positional.is_empty() {
+            return Err(USAGE.into());
+        }
+        let command = positional.remove(0);
+        Ok(Args {
+            command,
+            positional,
+            options,
+        })
+    }
+
+    fn all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.options
+            .iter()
+            .filter(move |(option, _)| option == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(option, _)| option == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn required(&self, name: &str) -> Result<&str> {
+        self.get(name)
+            .ok_or_else(|| format!("missing --{}", name).into())
+    }
+
+    fn parse_required<T: FromStr>(&self, name: &str) -> Result<T>
+    where
+        T::Err: std::error::Error + 'static,
+    {
+        Ok(self.required(name)?.parse()?)
+    }
+
+    fn positional(&self, index: usize) -> Result<&str> {
+        self.positional
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| format!("missing argument for {}", self.command).into())
+    }
+}
+
+fn main() {
+    if let Err(error) = run() {
+        eprintln!("error: {}", error);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<()> {
+    let args = Args::parse(env::args().skip(1))?;
+    let client = RpcClient::new(args.get("url").unwrap_or(DEFAULT_URL));
+
+    match args.command.as_str() {
+        "create-multisig" => create_multisig(&client, &keypair(&args)?, &args),
+        "propose" => propose(&client, &keypair(&args)?, &args, TransactionStatus::Active),
+        "draft" => propose(&client, &keypair(&args)?, &args, TransactionStatus::Draft),
+        "update" => update(&client, &keypair(&args)?, &args),
+        "activate" => act(
+            &client,
+            &keypair(&args)?,
+            &args,
+            MultisigInstruction::ActivateTransaction,
+        ),
+        "cancel" => act(
+            &client,
+            &keypair(&args)?,
+            &args,
+            MultisigInstruction::CancelTransaction,
+        ),
+        "approve" => approve(&client, &keypair(&args)?, &args),
+        "reject" => act(
+            &client,
+            &keypair(&args)?,
+            &args,
+            MultisigInstruction::RejectTransaction,
+        ),
+        "revoke" => act(
+            &client,
+            &keypair(&args)?,
+            &args,
+            MultisigInstruction::RevokeVote,
+        ),
+        "execute" => execute(&client, &keypair(&args)?, &args),
+        "list" => list(&client, &args),
+        "show-multisig" => show_multisig(&client, &Pubkey::from_str(args.positional(0)?)?),
+        "show-transaction" => show_transaction(&client, &Pubkey::from_str(args.positional(0)?)?),
+        "help" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        command => Err(format!("unknown command `{}`\n\n{}", command, USAGE).into()),
+    }
+}
+
+fn keypair(args: &Args) -> Result<Keypair> {
+    let path = match args.get("keypair") {
+        Some(path) => path.to_string(),
+        None => format!("{}/.config/solana/id.json", env::var("HOME")?),
+    };
+    read_keypair_file(&path).map_err(|e| format!("reading keypair {}: {}", path, e).into())
+}
+
+fn send(client: &RpcClient, payer: &Keypair, instruction: Instruction) -> Result<()> {
+    let transaction = solana_sdk::transaction::Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[payer],
+        client.latest_blockhash()?,
+    );
+    println!("Signature: {}", client.send_and_confirm(&transaction)?);
+    Ok(())
+}
+
+fn transaction_address(multisig: &Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            TRANSACTION_SEED.as_bytes(),
+            multisig.as_ref(),
+            &index.to_le_bytes(),
+        ],
+        &PROGRAM,
+    )
+}
+
+fn fetch_multisig(client: &RpcClient, key: &Pubkey) -> Result<(MultisigHeader, Vec<Pubkey>)> {
+    let data = client
+        .account_data(key)?
+        .ok_or_else(|| format!("multisig {} not found", key))?;
+    let (header, owners) =
+        Multisig::parse(&data).map_err(|_| format!("{} is not a multisig", key))?;
+    Ok((
+        *header,
+        owners.iter().map(|o| Pubkey::new_from_array(*o)).collect(),
+    ))
+}
+
+fn fetch_transaction(client: &RpcClient, key: &Pubkey) -> Result<Vec<u8>> {
+    let data = client
+        .account_data(key)?
+        .ok_or_else(|| format!("transaction {} not found", key))?;
+    TransactionHeader::load(&data).map_err(|_| format!("{} is not a transaction", key))?;
+    Ok(data)
+}
+
+fn create_multisig(client: &RpcClient, payer: &Keypair, args: &Args) -> Result<()> {
+    let seed: u64 = args.parse_required("seed")?;
+    let threshold: u64 = args.parse_required("threshold")?;
+
+    let mut owners = args
+        .all("owner")
+        .map(Pubkey::from_str)
+        .collect::<core::result::Result<Vec<_>, _>>()?;
+    owners.push(payer.pubkey());
+    owners.sort();
+    owners.dedup();
+    if owners.len() > MAX_OWNERS {
+        return Err(format!("at most {} owners are supported", MAX_OWNERS).into());
+    }
+
+    let executors = args
+        .all("executor")
+        .map(Pubkey::from_str)
+        .collect::<core::result::Result<Vec<_>, _>>()?;
+    if executors.len() > MAX_EXECUTORS {
+        return Err(format!("at most {} executors are supported", MAX_EXECUTORS).into());
+    }
+    let execute_policy = match args.get("execute-policy").unwrap_or("any-member") {
+        "any-member" => ExecutePolicy::AnyMember,
+        "executors" => ExecutePolicy::Executors,
+        "permissionless" => ExecutePolicy::Permissionless,
+        policy => return Err(format!("unknown execute policy `{}`", policy).into()),
+    };
+
+    let allowed = args
+        .all("allow-program")
+        .map(Pubkey::from_str)
+        .collect::<core::result::Result<Vec<_>, _>>()?;
+    let denied = args
+        .all("deny-program")
+        .map(Pubkey::from_str)
+        .collect::<core::result::Result<Vec<_>, _>>()?;
+    let (program_filter, filtered_programs) = match (allowed.is_empty(), denied.is_empty()) {
+        (true, true) => (ProgramFilter::None, allowed),
+        (false, true) => (ProgramFilter::Allowlist, allowed),
+        (true, false) => (ProgramFilter::Denylist, denied),
+        (false, false) => {
+            return Err("--allow-program and --deny-program are mutually exclusive".into())
+        }
+    };
+    if filtered_programs.len() > MAX_FILTERED_PROGRAMS {
+        return Err(format!(
+            "at most {} programs can be allowed or denied",
+            MAX_FILTERED_PROGRAMS
+        )
+        .into());
+    }
+
+    let seed_bytes = seed.to_le_bytes();
+    let (multisig, bump) =
+        Pubkey::find_program_address(&[MULTISIG_SEED.as_bytes(), &seed_bytes], &PROGRAM);
+
+    let mut header = MultisigHeader {
+        discriminator: MultisigHeader::DISCRIMINATOR,
+        seed: seed_bytes,
+        num_owners: (owners.len() as u64).to_le_bytes(),
+        threshold: threshold.to_le_bytes(),
+        nonce: 0,
+        bump,
+        guardian: match args.get("guardian") {
+            Some(guardian) => Pubkey::from_str(guardian)?.to_bytes(),
+            None => [0; 32],
+        },
+        pause_threshold: args
+            .get("pause-threshold")
+            .map_or(Ok(1), str::parse::<u64>)?
+            .to_le_bytes(),
+        paused: 0,
+        execute_policy: execute_policy as u8,
+        num_executors: executors.len() as u8,
+        executors: [[0; 32]; MAX_EXECUTORS],
+        transaction_index: [0; 8],
+        stale_transaction_index: [0; 8],
+        sorted_owners: 255,
+        num_threshold_policies: 0,
+        threshold_policies: [ThresholdPolicy::zeroed(); MAX_THRESHOLD_POLICIES],
+        program_filter: program_filter as u8,
+        num_filtered_programs: filtered_programs.len() as u8,
+        filtered_programs: [[0; 32]; MAX_FILTERED_PROGRAMS],
+    };
+    for (slot, executor) in header.executors.iter_mut().zip(&executors) {
+        *slot = executor.to_bytes();
+    }
+    for (slot, program) in header.filtered_programs.iter_mut().zip(&filtered_programs) {
+        *slot = program.to_bytes();
+    }
+
+    let mut data = vec![MultisigInstruction::CreateMultisig as u8];
+    data.extend_from_slice(bytemuck::bytes_of(&header));
+    for owner in &owners {
+        data.extend_from_slice(owner.as_ref());
+    }
+
+    send(
+        client,
+        payer,
+        Instruction::new_with_bytes(
+            PROGRAM,
+            &data,
+            vec![
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new(multisig, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+        ),
+    )?;
+    println!("Multisig: {}", multisig);
+    Ok(())
+}
+
+/// Parses the program, accounts, data and signing vaults of a transaction of
+/// `multisig` from `--program`, `--account`, `--data` and `--vault`.
+fn content(
+    args: &Args,
+    multisig: &Pubkey,
+) -> Result<(InlineTransactionHeader, Vec<TransactionAccount>, Vec<u8>)> {
+    let program_id = Pubkey::from_str(args.required("program")?)?;
+    let accounts = args
+        .all("account")
+        .map(|account| {
+            let mut parts = account.split(':');
+            let pubkey = Pubkey::from_str(parts.next().unwrap_or_default())?;
+            let flags: String = parts.collect();
+            Ok(TransactionAccount {
+                pubkey: pubkey.to_bytes(),
+                is_signer: flags.contains('s') as u8,
+                is_writable: flags.contains('w') as u8,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let tx_data = match args.get("data") {
+        Some(hex) => decode_hex(hex)?,
+        None => Vec::new(),
+    };
+
+    let mut vaults = TransactionVaults {
+        num_vaults: 0,
+        indices: [0; MAX_SIGNING_VAULTS],
+        bumps: [0; MAX_SIGNING_VAULTS],
+    };
+    let indices = match args.all("vault").collect::<Vec<_>>() {
+        indices if indices.is_empty() => vec![0],
+        indices => indices
+            .into_iter()
+            .map(str::parse::<u8>)
+            .collect::<core::result::Result<_, _>>()?,
+    };
+    if indices.len() > MAX_SIGNING_VAULTS {
+        return Err(format!("at most {} vaults can sign", MAX_SIGNING_VAULTS).into());
+    }
+    for (i, index) in indices.iter().enumerate() {
+        let (_, bump) = Multisig::vault_address(multisig.as_array(), *index)
+            .map_err(|_| "cannot derive vault address")?;
+        vaults.indices[i] = *index;
+        vaults.bumps[i] = bump;
+    }
+    vaults.num_vaults = indices.len() as u8;
+
+    let mut assertions = TransactionAssertions::zeroed();
+    let specs = args.all("assert").collect::<Vec<_>>();
+    if specs.len() > MAX_ASSERTIONS {
+        return Err(format!("at most {} assertions are supported", MAX_ASSERTIONS).into());
+    }
+    for (slot, spec) in assertions.assertions.iter_mut().zip(&specs) {
+        *slot = parse_assertion(spec)?;
+    }
+    assertions.num_assertions = specs.len() as u8;
+
+    let header = InlineTransactionHeader {
+        program_id: program_id.to_bytes(),
+        num_accounts: (accounts.len() as u64).to_le_bytes(),
+        data_len: (tx_data.len() as u64).to_le_bytes(),
+        vaults,
+        assertions,
+    };
+    Ok((header, accounts, tx_data))
+}
+
+/// Creates a transaction with the given status: `Active`, approved by the
+/// keypair, or `Draft`.
+fn propose(
+    client: &RpcClient,
+    payer: &Keypair,
+    args: &Args,
+    status: TransactionStatus,
+) -> Result<()> {
+    let multisig = Pubkey::from_str(args.required("multisig")?)?;
+    let (content, accounts, tx_data) = content(args, &multisig)?;
+    let draft = status == TransactionStatus::Draft;
+
+    let (multisig_header, owners) = fetch_multisig(client, &multisig)?;
+    let signers: Vec<TransactionSigner> = owners
+        .iter()
+        .map(|owner| TransactionSigner {
+            pubkey: owner.to_bytes(),
+            signed: if *owner == payer.pubkey() && !draft {
+                255
+            } else {
+                0
+            },
+            rejected: 0,
+        })
+        .collect();
+
+    let index = u64::from_le_bytes(multisig_header.transaction_index) + 1;
+    let (transaction, bump) = transaction_address(&multisig, index);
+    let header = TransactionHeader {
+        discriminator: TransactionHeader::DISCRIMINATOR,
+        multisig: multisig.to_bytes(),
+        proposer: [0; 32],
+        rent_payer: [0; 32],
+        program_id: content.program_id,
+        num_accounts: content.num_accounts,
+        num_signers: (signers.len() as u64).to_le_bytes(),
+        data_len: content.data_len,
+        status: status as u8,
+        approvals: [0; 8],
+        rejections: [0; 8],
+        index: index.to_le_bytes(),
+        bump,
+        proposal_hash: [0; 32],
+        vaults: content.vaults,
+        assertions: content.assertions,
+    };
+
+    let mut data = vec![MultisigInstruction::CreateTransaction as u8];
+    data.extend_from_slice(bytemuck::bytes_of(&header));
+    data.extend_from_slice(bytemuck::cast_slice::<TransactionAccount, u8>(&accounts));
+    data.extend_from_slice(bytemuck::cast_slice::<TransactionSigner, u8>(&signers));
+    data.extend_from_slice(&tx_data);
+
+    let mut metas = vec![
+        AccountMeta::new(payer.pubkey(), true),
+        AccountMeta::new(transaction, false),
+        AccountMeta::new(multisig, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    // The writable accounts of the transaction follow, to be checked.
+    metas.extend(
+        accounts
+            .iter()
+            .filter(|account| account.is_writable != 0)
+            .map(|account| AccountMeta::new(Pubkey::new_from_array(account.pubkey), false)),
+    );
+
+    send(
+        client,
+        payer,
+        Instruction::new_with_bytes(PROGRAM, &data, metas),
+    )?;
+    println!("Transaction: {}", transaction);
+    Ok(())
+}
+
+fn approve(client: &RpcClient, payer: &Keypair, args: &Args) -> Result<()> {
+    let transaction = Pubkey::from_str(args.required("transaction")?)?;
+    let data = fetch_transaction(client, &transaction)?;
+    let (header, accounts, _, tx_data) =
+        Transaction::parse(&data).map_err(|_| "invalid transaction data")?;
+    let multisig = Pubkey::new_from_array(header.multisig);
+
+    // Print what is being approved, and bind the approval to it.
+    let (multisig_header, _) = fetch_multisig(client, &multisig)?;
+    println!("{}", inspect(&data, &multisig_header)?);
+    let proposal_hash = Transaction::proposal_hash(
+        &header.program_id,
+        &header.vaults,
+        &header.assertions,
+        accounts,
+        tx_data,
+    );
+
+    let mut instruction_data = vec![MultisigInstruction::ApproveTransaction as u8];
+    instruction_data.extend_from_slice(&proposal_hash);
+
+    send(
+        client,
+        payer,
+        Instruction::new_with_bytes(
+            PROGRAM,
+            &instruction_data,
+            vec![
+                AccountMeta::new_readonly(payer.pubkey(), true),
+                AccountMeta::new(transaction, false),
+                AccountMeta::new_readonly(multisig, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+        ),
+    )
+}
+
+/// Replaces the content of a draft transaction.
+fn update(client: &RpcClient, payer: &Keypair, args: &Args) -> Result<()> {
+    let transaction = Pubkey::from_str(args.required("transaction")?)?;
+    let data = fetch_transaction(client, &transaction)?;
+    let (header, _, _, _) = Transaction::parse(&data).map_err(|_| "invalid transaction data")?;
+    let multisig = Pubkey::new_from_array(header.multisig);
+    let (content, accounts, tx_data) = content(args, &multisig)?;
+
+    let mut data = vec![MultisigInstruction::UpdateTransaction as u8];
+    data.extend_from_slice(bytemuck::bytes_of(&content));
+    data.extend_from_slice(bytemuck::cast_slice::<TransactionAccount, u8>(&accounts));
+    data.extend_from_slice(&tx_data);
+
+    let mut metas = vec![
+        AccountMeta::new(payer.pubkey(), true),
+        AccountMeta::new(transaction, false),
+        AccountMeta::new_readonly(multisig, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+    // The writable accounts of the transaction follow, to be checked.
+    metas.extend(
+        accounts
+            .iter()
+            .filter(|account| account.is_writable != 0)
+            .map(|account| AccountMeta::new(Pubkey::new_from_array(account.pubkey), false)),
+    );
+
+    send(
+        client,
+        payer,
+        Instruction::new_with_bytes(PROGRAM, &data, metas),
+    )
+}
+
+/// Sends `instruction` for a transaction, signed by the keypair: rejects,
+/// activates or cancels the transaction, or revokes the vote of the keypair
+/// on it.
+fn act(
+    client: &RpcClient,
+    payer: &Keypair,
+    args: &Args,
+    instruction: MultisigInstruction,
+) -> Result<()> {
+    let transaction = Pubkey::from_str(args.required("transaction")?)?;
+    let data = fetch_transaction(client, &transaction)?;
+    let (header, _, _, _) = Transaction::parse(&data).map_err(|_| "invalid transaction data")?;
+
+    send(
+        client,
+        payer,
+        Instruction::new_with_bytes(
+            PROGRAM,
+            &[instruction as u8],
+            vec![
+                AccountMeta::new_readonly(payer.pubkey(), true),
+                AccountMeta::new(transaction, false),
+                AccountMeta::new_readonly(Pubkey::new_from_array(header.multisig), false),
+            ],
+        ),
+    )
+}
+
+fn execute(client: &RpcClient, payer: &Keypair, args: &Args) -> Result<()> {
+    let transaction = Pubkey::from_str(args.required("transaction")?)?;
+    let data = fetch_transaction(client, &transaction)?;
+    let (header, accounts, _, _) =
+        Transaction::parse(&data).map_err(|_| "invalid transaction data")?;
+
+    let mut metas = vec![
+        AccountMeta::new_readonly(payer.pubkey(), true),
+        AccountMeta::new(transaction, false),
+        AccountMeta::new_readonly(Pubkey::new_from_array(header.multisig), false),
+        AccountMeta::new_readonly(Pubkey::new_from_array(header.program_id), false),
+    ];
+    // Vaults sign through the program; only the keypair can sign otherwise.
+    metas.extend(accounts.iter().map(|account| {
+        let pubkey = Pubkey::new_from_array(account.pubkey);
+        let is_signer = account.is_signer != 0 && pubkey == payer.pubkey();
+        if account.is_writable != 0 {
+            AccountMeta::new(pubkey, is_signer)
+        } else {
+            AccountMeta::new_readonly(pubkey, is_signer)
+        }
+    }));
+
+    send(
+        client,
+        payer,
+        Instruction::new_with_bytes(
+            PROGRAM,
+            &[MultisigInstruction::ExecuteTransaction as u8],
+            metas,
+        ),
+    )
+}
+
+fn list(client: &RpcClient, args: &Args) -> Result<()> {
+    let multisig = Pubkey::from_str(args.required("multisig")?)?;
+    let (multisig_header, _) = fetch_multisig(client, &multisig)?;
+
+    // Transactions that are stale can no longer be approved or executed.
+    let first = u64::from_le_bytes(multisig_header.stale_transaction_index) + 1;
+    let last = u64::from_le_bytes(multisig_header.transaction_index);
+    for index in first..=last {
+        let (key, _) = transaction_address(&multisig, index);
+        let Some(data) = client.account_data(&key)? else {
+            continue;
+        };
+        if TransactionHeader::load(&data).is_err() {
+            continue;
+        }
+        let summary = inspect(&data, &multisig_header)?;
+        if matches!(
+            summary.status,
+            TransactionStatus::Draft | TransactionStatus::Active | TransactionStatus::Approved
+        ) {
+            println!("Transaction: {}\n{}\n", key, summary);
+        }
+    }
+    Ok(())
+}
+
+fn show_multisig(client: &RpcClient, key: &Pubkey) -> Result<()> {
+    let (header, owners) = fetch_multisig(client, key)?;
+
+    println!("Multisig:        {}", key);
+    println!("Seed:            {}", u64::from_le_bytes(header.seed));
+    println!(
+        "Threshold:       {}/{}",
+        u64::from_le_bytes(header.threshold),
+        owners.len()
+    );
+    println!(
+        "Pause threshold: {}",
+        u64::from_le_bytes(header.pause_threshold)
+    );
+    if header.guardian != [0; 32] {
+        println!(
+            "Guardian:        {}",
+            Pubkey::new_from_array(header.guardian)
+        );
+    }
+    println!("Paused:          {}", header.is_paused());
+    println!(
+        "Transactions:    {} ({} stale)",
+        u64::from_le_bytes(header.transaction_index),
+        u64::from_le_bytes(header.stale_transaction_index)
+    );
+    let policy = match ExecutePolicy::try_from(header.execute_policy) {
+        Ok(ExecutePolicy::AnyMember) => "any member",
+        Ok(ExecutePolicy::Executors) => "executors",
+        Ok(ExecutePolicy::Permissionless) => "permissionless",
+        Err(_) => "invalid",
+    };
+    println!("Execute policy:  {}", policy);
+    for executor in header.executors() {
+        println!("  {}", Pubkey::new_from_array(*executor));
+    }
+    let filter = match ProgramFilter::try_from(header.program_filter) {
+        Ok(ProgramFilter::None) => "none",
+        Ok(ProgramFilter::Allowlist) => "allowlist",
+        Ok(ProgramFilter::Denylist) => "denylist",
+        Err(_) => "invalid",
+    };
+    println!("Program filter:  {}", filter);
+    for program in header.filtered_programs() {
+        println!("  {}", Pubkey::new_from_array(*program));
+    }
+    if !header.threshold_policies().is_empty() {
+        println!("Threshold policies:");
+    }
+    for policy in header.threshold_policies() {
+        let prefix: String = policy
+            .prefix()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        println!(
+            "  {} [{}] => {}",
+            Pubkey::new_from_array(policy.program_id),
+            prefix,
+            u64::from_le_bytes(policy.threshold)
+        );
+    }
+    let (vault, _) =
+        Multisig::vault_address(key.as_array(), 0).map_err(|_| "cannot derive vault address")?;
+    println!("Vault 0:         {}", Pubkey::new_from_array(vault));
+    println!("Owners:");
+    for owner in owners {
+        println!("  {}", owner);
+    }
+    Ok(())
+}
+
+fn show_transaction(client: &RpcClient, key: &Pubkey) -> Result<()> {
+    let data = fetch_transaction(client, key)?;
+    let (header, _, _, _) = Transaction::parse(&data).map_err(|_| "invalid transaction data")?;
+    let (multisig_header, _) = fetch_multisig(client, &Pubkey::new_from_array(header.multisig))?;
+
+    println!("Transaction: {}", key);
+    println!("{}", inspect(&data, &multisig_header)?);
+    Ok(())
+}
+
+fn inspect(data: &[u8], multisig: &MultisigHeader) -> Result<TransactionSummary> {
+    Transaction::inspect(data, multisig).map_err(|_| "invalid transaction data".into())
+}
+
+/// Parses an assertion given as `before|after:KIND:ACCOUNT:VALUE`.
+fn parse_assertion(spec: &str) -> Result<TransactionAssertion> {
+    let [when, kind, account, value] = spec.splitn(4, ':').collect::<Vec<_>>()[..] else {
+        return Err(format!("invalid assertion `{}`", spec).into());
+    };
+    let mut assertion = TransactionAssertion::zeroed();
+    assertion.after = match when {
+        "before" => 0,
+        "after" => 255,
+        _ => return Err(format!("invalid assertion `{}`", spec).into()),
+    };
+    let kind = match kind {
+        "lamports-min" => AssertionKind::LamportsAtLeast,
+        "lamports-max" => AssertionKind::LamportsAtMost,
+        "tokens-min" => AssertionKind::TokenAmountAtLeast,
+        "tokens-max" => AssertionKind::TokenAmountAtMost,
+        "data-hash" => AssertionKind::DataHashEquals,
+        kind => return Err(format!("unknown assertion kind `{}`", kind).into()),
+    };
+    assertion.kind = kind as u8;
+    assertion.account_index = account.parse()?;
+    if kind == AssertionKind::DataHashEquals {
+        assertion.value = decode_hex(value)?
+            .try_into()
+            .map_err(|_| "data hash must be 32 bytes")?;
+    } else {
+        assertion.value[..8].copy_from_slice(&value.parse::<u64>()?.to_le_bytes());
+    }
+    Ok(assertion)
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if !hex.is_ascii() {
+        return Err("hex data must be ASCII".into());
+    }
+    if !hex.len().is_multiple_of(2) {
+        return Err("hex data must have an even length".into());
+    }
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| Ok(u8::from_str_radix(std::str::from_utf8(pair)?, 16)?))
+        .collect()
+}
@@ -0,0 +1,92 @@
+use std::{error::Error, str::FromStr, thread, time::Duration};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Signature, transaction::Transaction};
+
+pub type Result<T> = core::result::Result<T, Box<dyn Error>>;
+
+/// Minimal JSON-RPC client for the handful of methods the CLI needs.
+pub struct RpcClient {
+    url: String,
+    http: reqwest::blocking::Client,
+}
+
+impl RpcClient {
+    pub fn new(url: &str) -> Self {
+        RpcClient {
+            url: url.to_string(),
+            http: reqwest::blocking::Client::new(),
+        }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let mut response: Value = self.http.post(&self.url).json(&request).send()?.json()?;
+        if let Some(error) = response.get("error") {
+            return Err(format!("{} failed: {}", method, error).into());
+        }
+        Ok(response["result"].take())
+    }
+
+    /// Returns the data of `key`, or `None` if the account does not exist.
+    pub fn account_data(&self, key: &Pubkey) -> Result<Option<Vec<u8>>> {
+        let result = self.call(
+            "getAccountInfo",
+            json!([key.to_string(), { "encoding": "base64" }]),
+        )?;
+        match &result["value"] {
+            Value::Null => Ok(None),
+            value => Ok(Some(decode_data(value)?)),
+        }
+    }
+
+    pub fn latest_blockhash(&self) -> Result<Hash> {
+        let result = self.call("getLatestBlockhash", json!([]))?;
+        let blockhash = result["value"]["blockhash"]
+            .as_str()
+            .ok_or("missing blockhash")?;
+        Ok(Hash::from_str(blockhash)?)
+    }
+
+    /// Sends a signed transaction and waits until it is confirmed.
+    pub fn send_and_confirm(&self, transaction: &Transaction) -> Result<Signature> {
+        let encoded = STANDARD.encode(bincode::serialize(transaction)?);
+        let result = self.call(
+            "sendTransaction",
+            json!([encoded, { "encoding": "base64" }]),
+        )?;
+        let signature = Signature::from_str(result.as_str().ok_or("missing signature")?)?;
+
+        for _ in 0..60 {
+            let result = self.call("getSignatureStatuses", json!([[signature.to_string()]]))?;
+            let status = &result["value"][0];
+            if !status.is_null() {
+                if !status["err"].is_null() {
+                    return Err(
+                        format!("transaction {} failed: {}", signature, status["err"]).into(),
+                    );
+                }
+                if matches!(
+                    status["confirmationStatus"].as_str(),
+                    Some("confirmed" | "finalized")
+                ) {
+                    return Ok(signature);
+                }
+            }
+            thread::sleep(Duration::from_millis(500));
+        }
+
+        Err(format!("transaction {} was not confirmed", signature).into())
+    }
+}
+
+fn decode_data(account: &Value) -> Result<Vec<u8>> {
+    let data = account["data"][0].as_str().ok_or("missing account data")?;
+    Ok(STANDARD.decode(data)?)
+}
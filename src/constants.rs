@@ -1,2 +1,45 @@
-pub const MULTISIG_SEED: &'static str = "multisig";
-pub const TRANSACTION_SEED: &'static str = "tx";
+use pinocchio::pubkey::Pubkey;
+
+pub const MULTISIG_SEED: &str = "multisig";
+pub const TRANSACTION_SEED: &str = "tx";
+pub const VAULT_SEED: &str = "vault";
+
+/// Domain separator of the message owners sign to approve off-chain.
+pub const APPROVAL_DOMAIN: &str = "p-multisig:approve";
+
+/// Address of the native ed25519 signature verification program.
+pub const ED25519_PROGRAM_ID: Pubkey =
+    pinocchio_pubkey::pubkey!("Ed25519SigVerify111111111111111111111111111");
+
+/// Address of the SPL Token program.
+pub const TOKEN_PROGRAM_ID: Pubkey =
+    pinocchio_pubkey::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// Address of the SPL Token 2022 program.
+pub const TOKEN_2022_PROGRAM_ID: Pubkey =
+    pinocchio_pubkey::pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// Maximum number of designated executors of a multisig.
+pub const MAX_EXECUTORS: usize = 4;
+
+/// Maximum number of threshold policies of a multisig.
+pub const MAX_THRESHOLD_POLICIES: usize = 4;
+
+/// Maximum length of the instruction data prefix matched by a threshold policy.
+pub const MAX_POLICY_PREFIX_LEN: usize = 8;
+
+/// Maximum number of programs in the allowlist or denylist of a multisig.
+pub const MAX_FILTERED_PROGRAMS: usize = 4;
+
+/// Maximum number of owners of a multisig. Keeps `CreateMultisig` within a
+/// single transaction and bounds the work of owner and signer checks.
+pub const MAX_OWNERS: usize = 16;
+
+/// Maximum number of assertions a transaction can carry.
+pub const MAX_ASSERTIONS: usize = 4;
+
+/// Maximum number of vaults that can sign a single transaction.
+pub const MAX_SIGNING_VAULTS: usize = 4;
+
+/// Maximum number of accounts a transaction can pass to the invoked program.
+pub const MAX_TRANSACTION_ACCOUNTS: usize = 32;
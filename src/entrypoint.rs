@@ -29,10 +29,36 @@ fn process_instruction(
             instruction::process_create_transaction(accounts, instruction_data)
         }
         MultisigInstruction::ApproveTransaction => {
-            instruction::process_approve_transaction(accounts)
+            instruction::process_approve_transaction(accounts, instruction_data)
         }
         MultisigInstruction::ExecuteTransaction => {
             instruction::process_execute_transaction(accounts)
         }
+        MultisigInstruction::PauseMultisig => instruction::process_pause_multisig(accounts),
+        MultisigInstruction::UnpauseMultisig => instruction::process_unpause_multisig(accounts),
+        MultisigInstruction::ApproveAndExecute => {
+            instruction::process_approve_and_execute(accounts, instruction_data)
+        }
+        MultisigInstruction::ExecuteInline => {
+            instruction::process_execute_inline(accounts, instruction_data)
+        }
+        MultisigInstruction::ApproveWithSignatures => {
+            instruction::process_approve_with_signatures(accounts)
+        }
+        MultisigInstruction::RejectTransaction => instruction::process_reject_transaction(accounts),
+        MultisigInstruction::RevokeVote => instruction::process_revoke_vote(accounts),
+        MultisigInstruction::UpdateTransaction => {
+            instruction::process_update_transaction(accounts, instruction_data)
+        }
+        MultisigInstruction::ActivateTransaction => {
+            instruction::process_activate_transaction(accounts)
+        }
+        MultisigInstruction::SetThresholdPolicies => {
+            instruction::process_set_threshold_policies(accounts, instruction_data)
+        }
+        MultisigInstruction::SetProgramFilter => {
+            instruction::process_set_program_filter(accounts, instruction_data)
+        }
+        MultisigInstruction::CancelTransaction => instruction::process_cancel_transaction(accounts),
     }
 }
@@ -4,6 +4,33 @@ use pinocchio::program_error::ProgramError;
 pub enum CustomError {
     IncrementError,
     DecrementError,
+    /// The multisig is paused and does not accept any activity.
+    MultisigPaused,
+    /// The multisig is not paused.
+    MultisigNotPaused,
+    /// Not enough owners (or the guardian) signed the instruction.
+    NotEnoughSigners,
+    /// An account passed to execution does not match the approved account list.
+    AccountMismatch,
+    /// The signer is not allowed to execute transactions of this multisig.
+    UnauthorizedExecutor,
+    /// The ed25519 signature verification instruction is missing or does not
+    /// match the transaction being approved.
+    InvalidSignatures,
+    /// The transaction does not match the proposal hash expected by the approver.
+    ProposalHashMismatch,
+    /// The transaction was created before the last configuration change of
+    /// the multisig.
+    StaleTransaction,
+    /// The status of the transaction does not allow the operation.
+    InvalidTransactionStatus,
+    /// The program to invoke is not allowed by the program filter of the
+    /// multisig.
+    ProgramNotAllowed,
+    /// An assertion of the transaction does not hold.
+    AssertionFailed,
+    /// A transaction lists a state account of its own multisig as writable.
+    ProtectedAccount,
 }
 
 impl From<CustomError> for ProgramError {
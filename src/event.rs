@@ -0,0 +1,148 @@
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{log::sol_log_data, program_error::ProgramError, pubkey::Pubkey};
+
+/// Version of the event schema. Bumped whenever the layout of [`Event`] changes.
+pub const EVENT_VERSION: u8 = 1;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EventKind {
+    CreateMultisig,
+    CreateTransaction,
+    Approve,
+    Execute,
+    Pause,
+    Unpause,
+    Reject,
+    Revoke,
+    Update,
+    Activate,
+    SetThresholdPolicies,
+    SetProgramFilter,
+    Cancel,
+}
+
+impl TryFrom<u8> for EventKind {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(EventKind::CreateMultisig),
+            1 => Ok(EventKind::CreateTransaction),
+            2 => Ok(EventKind::Approve),
+            3 => Ok(EventKind::Execute),
+            4 => Ok(EventKind::Pause),
+            5 => Ok(EventKind::Unpause),
+            6 => Ok(EventKind::Reject),
+            7 => Ok(EventKind::Revoke),
+            8 => Ok(EventKind::Update),
+            9 => Ok(EventKind::Activate),
+            10 => Ok(EventKind::SetThresholdPolicies),
+            11 => Ok(EventKind::SetProgramFilter),
+            12 => Ok(EventKind::Cancel),
+            _ => Err(ProgramError::InvalidArgument),
+        }
+    }
+}
+
+/// Fixed-size event emitted through `sol_log_data` on every state transition.
+///
+/// Fields that do not apply to an event (e.g. `transaction` for
+/// `CreateMultisig`, or `actor` for instructions authorized by several owners
+/// at once) are zeroed.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct Event {
+    pub version: u8,
+    pub discriminator: u8,
+    pub multisig: Pubkey,
+    pub transaction: Pubkey,
+    pub actor: Pubkey,
+    pub approval_count: [u8; 8],
+}
+
+impl Event {
+    pub fn new(
+        kind: EventKind,
+        multisig: &Pubkey,
+        transaction: &Pubkey,
+        actor: &Pubkey,
+        approval_count: u64,
+    ) -> Self {
+        Event {
+            version: EVENT_VERSION,
+            discriminator: kind as u8,
+            multisig: *multisig,
+            transaction: *transaction,
+            actor: *actor,
+            approval_count: approval_count.to_le_bytes(),
+        }
+    }
+
+    /// Logs the event as a single `Program data:` entry.
+    pub fn emit(&self) {
+        sol_log_data(&[bytemuck::bytes_of(self)]);
+    }
+
+    /// Decodes an event from its binary representation.
+    pub fn decode(data: &[u8]) -> Result<Event, ProgramError> {
+        let event = bytemuck::try_pod_read_unaligned::<Event>(data)
+            .map_err(|_| ProgramError::InvalidArgument)?;
+        if event.version != EVENT_VERSION {
+            return Err(ProgramError::InvalidArgument);
+        }
+        EventKind::try_from(event.discriminator)?;
+        Ok(event)
+    }
+
+    pub fn kind(&self) -> Result<EventKind, ProgramError> {
+        EventKind::try_from(self.discriminator)
+    }
+}
+
+#[cfg(feature = "std")]
+mod decoder {
+    use super::Event;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use std::{string::String, vec::Vec};
+
+    /// Extracts the events emitted by this program from a transaction's log
+    /// messages.
+    ///
+    /// `Program data:` entries are attributed to the program that is executing
+    /// at that point, so data logged by other programs (including programs
+    /// invoked by an executed transaction) is skipped.
+    pub fn decode_events(logs: &[String]) -> Vec<Event> {
+        let program_id = bs58::encode(crate::ID).into_string();
+        let mut stack: Vec<&str> = Vec::new();
+        let mut events = Vec::new();
+
+        for log in logs {
+            let Some(rest) = log.strip_prefix("Program ") else {
+                continue;
+            };
+            if let Some(data) = rest.strip_prefix("data: ") {
+                if stack.last() != Some(&program_id.as_str()) {
+                    continue;
+                }
+                let Ok(bytes) = STANDARD.decode(data.trim()) else {
+                    continue;
+                };
+                if let Ok(event) = Event::decode(&bytes) {
+                    events.push(event);
+                }
+            } else if let Some((id, status)) = rest.split_once(' ') {
+                if status.starts_with("invoke") {
+                    stack.push(id);
+                } else if status == "success" || status.starts_with("failed") {
+                    stack.pop();
+                }
+            }
+        }
+
+        events
+    }
+}
+
+#[cfg(feature = "std")]
+pub use decoder::decode_events;
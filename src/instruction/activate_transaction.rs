@@ -0,0 +1,66 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{
+    error::CustomError,
+    event::{Event, EventKind},
+    state::{Multisig, Transaction, TransactionStatus},
+};
+
+/// Freezes the content of a draft transaction and opens it to votes, counting
+/// the approval of its proposer.
+pub fn process_activate_transaction(accounts: &[AccountInfo]) -> ProgramResult {
+    let [proposer, transaction, multisig] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !proposer.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (transaction_header, _, _, tx_data) = Transaction::read(transaction)?;
+    let (multisig_header, _) = Multisig::read(multisig)?;
+
+    // Check if multisig is paused.
+    if multisig_header.is_paused() {
+        return Err(CustomError::MultisigPaused.into());
+    }
+
+    // Check that the transaction belongs to the multisig.
+    if transaction_header.multisig != *multisig.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Check if the transaction predates a configuration change.
+    if multisig_header.is_stale(&transaction_header.index) {
+        return Err(CustomError::StaleTransaction.into());
+    }
+
+    // Only drafts can be activated, and only by their proposer.
+    if transaction_header.status()? != TransactionStatus::Draft {
+        return Err(CustomError::InvalidTransactionStatus.into());
+    }
+    if transaction_header.proposer != *proposer.key() {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+
+    let threshold = multisig_header.threshold_for(&transaction_header.program_id, tx_data);
+
+    // Update the signed status for the proposer.
+    let (transaction_header, signers) = Transaction::load_signers_mut(transaction)?;
+    let Some(signer_entry) = signers.iter_mut().find(|s| s.pubkey == *proposer.key()) else {
+        return Err(ProgramError::InvalidAccountData);
+    };
+    signer_entry.signed = 255;
+    transaction_header.add_vote(true, threshold)?;
+
+    Event::new(
+        EventKind::Activate,
+        multisig.key(),
+        transaction.key(),
+        proposer.key(),
+        u64::from_le_bytes(transaction_header.approvals),
+    )
+    .emit();
+
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{
+    instruction::{process_approve_transaction, process_execute_transaction},
+    state::{Multisig, Transaction, TransactionStatus},
+};
+
+/// Approves a transaction and, if this approval meets the threshold and the
+/// approver may execute, executes it in the same instruction.
+///
+/// Takes the same accounts as `ExecuteTransaction`, with the approving owner
+/// as the executor, and the same data as `ApproveTransaction`.
+pub fn process_approve_and_execute(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [user, transaction, multisig, _program, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    process_approve_transaction(&accounts[..4], data)?;
+
+    let (transaction_header, _, _, _) = Transaction::read(transaction)?;
+    if transaction_header.status()? != TransactionStatus::Approved {
+        return Ok(());
+    }
+
+    // Approvers that may not execute only record their approval.
+    let (multisig_header, owners) = Multisig::read(multisig)?;
+    if !multisig_header.can_execute(owners, user.key())? {
+        return Ok(());
+    }
+
+    process_execute_transaction(accounts)
+}
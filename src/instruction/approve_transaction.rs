@@ -1,11 +1,17 @@
-use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey, ProgramResult};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
 
 use crate::{
-    constants::{MULTISIG_SEED, TRANSACTION_SEED},
+    error::CustomError,
+    event::{Event, EventKind},
     state::{Multisig, Transaction},
 };
 
-pub fn process_approve_transaction(accounts: &[AccountInfo]) -> ProgramResult {
+/// Approves a transaction. `data` optionally holds the proposal hash the
+/// owner reviewed, in which case the approval fails if it does not match.
+///
+/// The owner may be the vault of another multisig, signing through the
+/// execution of one of its transactions.
+pub fn process_approve_transaction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
     let [user, transaction, multisig, _system_program] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
@@ -14,53 +20,68 @@ pub fn process_approve_transaction(accounts: &[AccountInfo]) -> ProgramResult {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    let (transaction_header, _, _, _) = Transaction::read(transaction)?;
+    let (transaction_header, _, _, tx_data) = Transaction::read(transaction)?;
     let (multisig_header, owners) = Multisig::read(multisig)?;
 
-    // Validate multisig account.
-    let multisig_pda = pubkey::create_program_address(
-        &[
-            MULTISIG_SEED.as_bytes(),
-            multisig_header.seed.as_ref(),
-            &[multisig_header.bump as u8],
-        ],
-        &crate::ID,
-    )?;
-    if multisig.key() != &multisig_pda {
-        return Err(ProgramError::InvalidAccountData);
+    // Check if multisig is paused.
+    if multisig_header.is_paused() {
+        return Err(CustomError::MultisigPaused.into());
     }
 
-    // Validate transaction account.
-    let transaction_pda = pubkey::create_program_address(
-        &[
-            TRANSACTION_SEED.as_bytes(),
-            transaction_header.seed.as_ref(),
-            &[transaction_header.bump as u8],
-        ],
-        &crate::ID,
-    )?;
-    if transaction.key() != &transaction_pda {
+    // Check that the transaction belongs to the multisig.
+    if transaction_header.multisig != *multisig.key() {
         return Err(ProgramError::InvalidAccountData);
     }
 
+    // Check if the transaction predates a configuration change.
+    if multisig_header.is_stale(&transaction_header.index) {
+        return Err(CustomError::StaleTransaction.into());
+    }
+
+    // Check that the transaction is open to votes.
+    if !transaction_header.is_open()? {
+        return Err(CustomError::InvalidTransactionStatus.into());
+    }
+
+    // Check the proposal hash, if provided.
+    match data.len() {
+        0 => {}
+        32 => {
+            if data != transaction_header.proposal_hash {
+                return Err(CustomError::ProposalHashMismatch.into());
+            }
+        }
+        _ => return Err(ProgramError::InvalidInstructionData),
+    }
+
     // Check if user is in the list of multisig owners.
-    if !owners.iter().any(|k| k.eq(user.key())) {
+    if !multisig_header.is_owner(owners, user.key()) {
         return Err(ProgramError::InvalidInstructionData);
     }
 
+    let threshold = multisig_header.threshold_for(&transaction_header.program_id, tx_data);
+
     // Update the signed status for the user.
-    let signers = Transaction::signers(transaction)?;
-    for signer_entry in signers.iter_mut() {
-        if signer_entry.pubkey == *user.key() {
-            if signer_entry.signed == 255 {
-                // User has already approved the transaction.
-                return Err(ProgramError::InvalidInstructionData);
-            } else {
-                signer_entry.signed = 255;
-                break;
-            }
-        }
+    let (transaction_header, signers) = Transaction::load_signers_mut(transaction)?;
+    let Some(signer_entry) = signers.iter_mut().find(|s| s.pubkey == *user.key()) else {
+        return Err(ProgramError::InvalidInstructionData);
+    };
+    if signer_entry.signed == 255 || signer_entry.rejected == 255 {
+        // User has already voted on the transaction.
+        return Err(ProgramError::InvalidInstructionData);
     }
+    signer_entry.signed = 255;
+    transaction_header.add_vote(true, threshold)?;
+
+    let approval_count = u64::from_le_bytes(transaction_header.approvals);
+    Event::new(
+        EventKind::Approve,
+        multisig.key(),
+        transaction.key(),
+        user.key(),
+        approval_count,
+    )
+    .emit();
 
     Ok(())
 }
@@ -0,0 +1,125 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey,
+    sysvars::instructions::Instructions, ProgramResult,
+};
+
+use crate::{
+    constants::ED25519_PROGRAM_ID,
+    error::CustomError,
+    event::{Event, EventKind},
+    state::{Multisig, Transaction},
+};
+
+/// Offset of the first signature offsets entry in ed25519 program data.
+const SIGNATURE_OFFSETS_START: usize = 2;
+
+/// Size of a signature offsets entry in ed25519 program data.
+const SIGNATURE_OFFSETS_SIZE: usize = 14;
+
+/// Approves a transaction on behalf of every owner whose signature of the
+/// approval message is verified by the ed25519 program instruction that
+/// immediately precedes this one.
+pub fn process_approve_with_signatures(accounts: &[AccountInfo]) -> ProgramResult {
+    let [transaction, multisig, instructions_sysvar] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let (transaction_header, _, _, tx_data) = Transaction::read(transaction)?;
+    let (multisig_header, owners) = Multisig::read(multisig)?;
+
+    // Check if multisig is paused.
+    if multisig_header.is_paused() {
+        return Err(CustomError::MultisigPaused.into());
+    }
+
+    // Check that the transaction belongs to the multisig.
+    if transaction_header.multisig != *multisig.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Check if the transaction predates a configuration change.
+    if multisig_header.is_stale(&transaction_header.index) {
+        return Err(CustomError::StaleTransaction.into());
+    }
+
+    // Check that the transaction is open to votes.
+    if !transaction_header.is_open()? {
+        return Err(CustomError::InvalidTransactionStatus.into());
+    }
+
+    // Message every owner must have signed.
+    let message =
+        Transaction::approval_message(transaction.key(), &transaction_header.proposal_hash);
+
+    // Load the signature verification instruction.
+    let instructions = Instructions::try_from(instructions_sysvar)?;
+    let ed25519_instruction = instructions.get_instruction_relative(-1)?;
+    if ed25519_instruction.get_program_id() != &ED25519_PROGRAM_ID {
+        return Err(CustomError::InvalidSignatures.into());
+    }
+    let ed25519_data = ed25519_instruction.get_instruction_data();
+    let num_signatures = *ed25519_data.first().ok_or(CustomError::InvalidSignatures)? as usize;
+    if num_signatures == 0 {
+        return Err(CustomError::InvalidSignatures.into());
+    }
+
+    let threshold = multisig_header.threshold_for(&transaction_header.program_id, tx_data);
+    let (header, signers) = Transaction::load_signers_mut(transaction)?;
+
+    for index in 0..num_signatures {
+        let start = SIGNATURE_OFFSETS_START + index * SIGNATURE_OFFSETS_SIZE;
+        let offsets = ed25519_data
+            .get(start..start + SIGNATURE_OFFSETS_SIZE)
+            .ok_or(CustomError::InvalidSignatures)?;
+        let read_u16 = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]);
+
+        // The public key and message must be read from the ed25519
+        // instruction itself, where they were verified.
+        if read_u16(6) != u16::MAX || read_u16(12) != u16::MAX {
+            return Err(CustomError::InvalidSignatures.into());
+        }
+        let public_key_offset = read_u16(4) as usize;
+        let message_offset = read_u16(8) as usize;
+        let message_size = read_u16(10) as usize;
+
+        let owner: &Pubkey = ed25519_data
+            .get(public_key_offset..public_key_offset + 32)
+            .and_then(|key| key.try_into().ok())
+            .ok_or(CustomError::InvalidSignatures)?;
+        let signed_message = ed25519_data
+            .get(message_offset..message_offset + message_size)
+            .ok_or(CustomError::InvalidSignatures)?;
+        if signed_message != message {
+            return Err(CustomError::InvalidSignatures.into());
+        }
+
+        // Check if the signer is in the list of multisig owners.
+        if !multisig_header.is_owner(owners, owner) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // Update the signed status, skipping owners that already approved.
+        let Some(signer_entry) = signers.iter_mut().find(|s| s.pubkey == *owner) else {
+            return Err(ProgramError::InvalidInstructionData);
+        };
+        if signer_entry.signed == 255 {
+            continue;
+        }
+        if signer_entry.rejected == 255 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        signer_entry.signed = 255;
+        header.add_vote(true, threshold)?;
+
+        Event::new(
+            EventKind::Approve,
+            multisig.key(),
+            transaction.key(),
+            &signer_entry.pubkey,
+            u64::from_le_bytes(header.approvals),
+        )
+        .emit();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{
+    error::CustomError,
+    event::{Event, EventKind},
+    state::{AccountState, Multisig, Transaction, TransactionHeader, TransactionStatus},
+};
+
+/// Withdraws a draft, or a transaction still collecting votes, on behalf of
+/// its proposer. Cancelled transactions can no longer be voted on or executed.
+pub fn process_cancel_transaction(accounts: &[AccountInfo]) -> ProgramResult {
+    let [proposer, transaction, multisig] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !proposer.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (transaction_header, _, _, _) = Transaction::read(transaction)?;
+    let (multisig_header, _) = Multisig::read(multisig)?;
+
+    // Check if multisig is paused.
+    if multisig_header.is_paused() {
+        return Err(CustomError::MultisigPaused.into());
+    }
+
+    // Check that the transaction belongs to the multisig.
+    if transaction_header.multisig != *multisig.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Only drafts and active transactions can be cancelled, and only by their
+    // proposer. Stale transactions can still be withdrawn.
+    if !matches!(
+        transaction_header.status()?,
+        TransactionStatus::Draft | TransactionStatus::Active
+    ) {
+        return Err(CustomError::InvalidTransactionStatus.into());
+    }
+    if transaction_header.proposer != *proposer.key() {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+
+    let (transaction_header, _) = TransactionHeader::load_account_mut(transaction)?;
+    transaction_header.status = TransactionStatus::Cancelled as u8;
+
+    Event::new(
+        EventKind::Cancel,
+        multisig.key(),
+        transaction.key(),
+        proposer.key(),
+        u64::from_le_bytes(transaction_header.approvals),
+    )
+    .emit();
+
+    Ok(())
+}
@@ -1,15 +1,20 @@
 use pinocchio::{
     account_info::AccountInfo,
+    instruction::Signer,
     program_error::ProgramError,
-    pubkey,
-    sysvars::{rent::Rent, Sysvar},
-    ProgramResult,
+    pubkey::{self, Pubkey},
+    seeds, ProgramResult,
 };
 
-use crate::{constants::MULTISIG_SEED, state::Multisig};
+use crate::{
+    constants::{MAX_EXECUTORS, MULTISIG_SEED},
+    event::{Event, EventKind},
+    state::{AccountState, ExecutePolicy, Multisig},
+    utils::create_pda_account,
+};
 
 pub fn process_create_multisig(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
-    let [user, multisig, _system_program] = accounts else {
+    let [user, multisig, _system_program, remaining @ ..] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
@@ -17,46 +22,115 @@ pub fn process_create_multisig(accounts: &[AccountInfo], data: &[u8]) -> Program
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    let (multisig_header, owners) = Multisig::parse(&data)?;
+    // Rent is paid by the optional payer account, or by the user otherwise.
+    let payer = match remaining {
+        [] => user,
+        [payer, ..] => {
+            if !payer.is_signer() {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if !payer.is_writable() {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            payer
+        }
+    };
+
+    let (multisig_header, owners) = Multisig::parse(data)?;
 
-    // Validate multisig account.
-    let multisig_pda = pubkey::create_program_address(
-        &[
-            MULTISIG_SEED.as_bytes(),
-            multisig_header.seed.as_ref(),
-            &[multisig_header.bump as u8],
-        ],
+    // Validate multisig account, which must use the canonical bump.
+    let (multisig_pda, bump) = pubkey::try_find_program_address(
+        &[MULTISIG_SEED.as_bytes(), multisig_header.seed.as_ref()],
         &crate::ID,
-    )?;
-    if multisig.key() != &multisig_pda {
+    )
+    .ok_or(ProgramError::InvalidSeeds)?;
+    if multisig.key() != &multisig_pda || multisig_header.bump != bump {
         return Err(ProgramError::InvalidAccountData);
     }
 
-    // Validate owners.
+    // Validate owners: exactly `num_owners` of them, at most `MAX_OWNERS`
+    // (checked on parse), without duplicates, and in strictly ascending order
+    // if declared sorted.
+    if data.len() != multisig_header.account_size()? {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let unique = match multisig_header.sorted_owners {
+        255 => owners.windows(2).all(|pair| pair[0] < pair[1]),
+        0 => owners
+            .iter()
+            .enumerate()
+            .all(|(i, owner)| !owners[..i].contains(owner)),
+        _ => false,
+    };
+    if !unique {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // Validate threshold.
     let threshold = u64::from_le_bytes(multisig_header.threshold) as usize;
-    if !(threshold > 0 && owners.len() > 0 && threshold <= owners.len()) {
+    if !(threshold > 0 && !owners.is_empty() && threshold <= owners.len()) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // Validate pause settings.
+    let pause_threshold = u64::from_le_bytes(multisig_header.pause_threshold) as usize;
+    if !(pause_threshold > 0 && pause_threshold <= threshold) || multisig_header.is_paused() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // Validate execute policy.
+    let execute_policy = ExecutePolicy::try_from(multisig_header.execute_policy)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    let num_executors = multisig_header.num_executors as usize;
+    if num_executors > MAX_EXECUTORS
+        || (execute_policy == ExecutePolicy::Executors && num_executors == 0)
+    {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // Validate threshold policies.
+    multisig_header.validate_threshold_policies()?;
+
+    // Validate program filter.
+    multisig_header.validate_program_filter()?;
+
+    // Transaction numbering starts from zero.
+    if multisig_header.transaction_index != [0; 8]
+        || multisig_header.stale_transaction_index != [0; 8]
+    {
         return Err(ProgramError::InvalidInstructionData);
     }
 
     // Check if user is in the list of owners.
-    if !owners.iter().any(|k| k.eq(user.key())) {
+    if !multisig_header.is_owner(owners, user.key()) {
         return Err(ProgramError::InvalidInstructionData);
     }
 
     // Create multisig account.
-    let num_owners = u64::from_le_bytes(multisig_header.num_owners);
-    let size = Multisig::size(num_owners);
-    pinocchio_system::instructions::CreateAccount {
-        from: user,
-        to: multisig,
-        space: size as u64,
-        lamports: Rent::get()?.minimum_balance(size),
-        owner: &crate::ID,
-    }
-    .invoke()?;
+    let bump = [multisig_header.bump];
+    let seeds = seeds!(
+        MULTISIG_SEED.as_bytes(),
+        multisig_header.seed.as_ref(),
+        &bump
+    );
+    create_pda_account(
+        payer,
+        multisig,
+        multisig_header.account_size()?,
+        Signer::from(&seeds),
+    )?;
 
     // Write data to multisig account.
     Multisig::write(multisig, multisig_header, owners)?;
 
+    Event::new(
+        EventKind::CreateMultisig,
+        multisig.key(),
+        &Pubkey::default(),
+        user.key(),
+        0,
+    )
+    .emit();
+
     Ok(())
 }
@@ -1,18 +1,24 @@
 use pinocchio::{
-    account_info::AccountInfo,
-    program_error::ProgramError,
-    pubkey,
-    sysvars::{rent::Rent, Sysvar},
+    account_info::AccountInfo, instruction::Signer, program_error::ProgramError, pubkey, seeds,
     ProgramResult,
 };
 
 use crate::{
-    constants::{MULTISIG_SEED, TRANSACTION_SEED},
-    state::{Multisig, Transaction},
+    constants::{MAX_TRANSACTION_ACCOUNTS, TRANSACTION_SEED},
+    error::CustomError,
+    event::{Event, EventKind},
+    state::{AccountState, Multisig, MultisigHeader, Transaction, TransactionStatus},
+    utils::{check_invocation, check_writable_accounts, create_pda_account},
 };
 
+/// Creates a transaction approved by its creator or, if its status is
+/// `Draft`, a draft that its creator can edit until it is activated.
+///
+/// The optional payer follows the system program, and the writable accounts
+/// of the transaction follow in order, so that none of them can be a
+/// transaction of the multisig.
 pub fn process_create_transaction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
-    let [user, transaction, multisig, _system_program] = accounts else {
+    let [user, transaction, multisig, _system_program, remaining @ ..] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
@@ -20,37 +26,105 @@ pub fn process_create_transaction(accounts: &[AccountInfo], data: &[u8]) -> Prog
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    let (transaction_header, accounts, signers, tx_data) = Transaction::parse(&data)?;
+    let (transaction_header, accounts, signers, tx_data) = Transaction::parse(data)?;
     let (multisig_header, owners) = Multisig::read(multisig)?;
 
-    // Validate multisig account.
-    let multisig_pda = pubkey::create_program_address(
-        &[
-            MULTISIG_SEED.as_bytes(),
-            multisig_header.seed.as_ref(),
-            &[multisig_header.bump as u8],
-        ],
-        &crate::ID,
-    )?;
-    if multisig.key() != &multisig_pda {
-        return Err(ProgramError::InvalidAccountData);
+    // Rent is paid by the optional payer account, or by the user otherwise.
+    let num_writable = accounts
+        .iter()
+        .filter(|account| account.is_writable != 0)
+        .count();
+    let (payer, writable_accounts) = match remaining.len().checked_sub(num_writable) {
+        Some(0) => (user, remaining),
+        Some(1) => {
+            let payer = &remaining[0];
+            if !payer.is_signer() {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if !payer.is_writable() {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            (payer, &remaining[1..])
+        }
+        Some(_) => return Err(ProgramError::InvalidArgument),
+        None => return Err(ProgramError::NotEnoughAccountKeys),
+    };
+
+    // Check if multisig is paused.
+    if multisig_header.is_paused() {
+        return Err(CustomError::MultisigPaused.into());
     }
 
-    // Validate transaction account.
-    let transaction_pda = pubkey::create_program_address(
+    // Validate transaction account, which must use the canonical bump.
+    let (transaction_pda, bump) = pubkey::try_find_program_address(
         &[
             TRANSACTION_SEED.as_bytes(),
-            transaction_header.seed.as_ref(),
-            &[transaction_header.bump as u8],
+            multisig.key().as_ref(),
+            transaction_header.index.as_ref(),
         ],
         &crate::ID,
-    )?;
-    if transaction.key() != &transaction_pda {
+    )
+    .ok_or(ProgramError::InvalidSeeds)?;
+    if transaction.key() != &transaction_pda
+        || transaction_header.bump != bump
+        || transaction_header.multisig != *multisig.key()
+    {
         return Err(ProgramError::InvalidAccountData);
     }
 
+    // Transactions are numbered sequentially within the multisig.
+    let index = u64::from_le_bytes(multisig_header.transaction_index)
+        .checked_add(1)
+        .ok_or(CustomError::IncrementError)?;
+    if u64::from_le_bytes(transaction_header.index) != index {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // Check that the multisig may invoke the program.
+    if !multisig_header.is_program_allowed(&transaction_header.program_id)? {
+        return Err(CustomError::ProgramNotAllowed.into());
+    }
+
+    // Check that the transaction does not expose the state of the multisig.
+    check_invocation(
+        multisig.key(),
+        &transaction_header.program_id,
+        accounts,
+        tx_data,
+    )?;
+    if accounts
+        .iter()
+        .any(|account| account.is_writable != 0 && account.pubkey == *transaction.key())
+    {
+        return Err(CustomError::ProtectedAccount.into());
+    }
+    check_writable_accounts(multisig.key(), accounts, writable_accounts)?;
+
+    // Check that the transaction can be invoked.
+    if accounts.len() > MAX_TRANSACTION_ACCOUNTS {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // Validate signing vaults.
+    transaction_header.vaults.validate(multisig.key())?;
+
+    // Validate assertions.
+    transaction_header.assertions.validate(accounts.len())?;
+
     // Check if user is in the list of multisig owners.
-    if !owners.iter().any(|k| k.eq(user.key())) {
+    if !multisig_header.is_owner(owners, user.key()) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // Transactions are created either as drafts or open to votes.
+    let draft = match transaction_header.status()? {
+        TransactionStatus::Draft => true,
+        TransactionStatus::Active => false,
+        _ => return Err(ProgramError::InvalidInstructionData),
+    };
+
+    // There must be exactly one signer entry per owner.
+    if signers.len() != owners.len() {
         return Err(ProgramError::InvalidInstructionData);
     }
 
@@ -58,12 +132,15 @@ pub fn process_create_transaction(accounts: &[AccountInfo], data: &[u8]) -> Prog
     for owner in owners {
         match signers.iter().find(|s| s.pubkey == *owner) {
             Some(signer_entry) => {
-                // If this owner is the transaction creator, check that they have signed.
-                if owner == user.key() && signer_entry.signed != 255 {
-                    return Err(ProgramError::InvalidInstructionData);
-                }
-                // Other owners must not have signed yet (0)
-                else if owner != user.key() && signer_entry.signed != 0 {
+                // The transaction creator must have signed unless it is a
+                // draft, other owners must not have signed yet (0). Nobody has
+                // rejected it.
+                let expected = if owner == user.key() && !draft {
+                    255
+                } else {
+                    0
+                };
+                if signer_entry.signed != expected || signer_entry.rejected != 0 {
                     return Err(ProgramError::InvalidInstructionData);
                 }
             }
@@ -75,21 +152,51 @@ pub fn process_create_transaction(accounts: &[AccountInfo], data: &[u8]) -> Prog
     }
 
     // Create transaction account.
-    let num_accounts = u64::from_le_bytes(transaction_header.num_accounts);
-    let num_signers = u64::from_le_bytes(transaction_header.num_signers);
-    let data_len = u64::from_le_bytes(transaction_header.data_len);
-    let size = Transaction::size(num_accounts, num_signers, data_len);
-    pinocchio_system::instructions::CreateAccount {
-        from: user,
-        to: transaction,
-        space: size as u64,
-        lamports: Rent::get()?.minimum_balance(size),
-        owner: &crate::ID,
-    }
-    .invoke()?;
+    let bump = [transaction_header.bump];
+    let seeds = seeds!(
+        TRANSACTION_SEED.as_bytes(),
+        multisig.key().as_ref(),
+        transaction_header.index.as_ref(),
+        &bump
+    );
+    create_pda_account(
+        payer,
+        transaction,
+        transaction_header.account_size()?,
+        Signer::from(&seeds),
+    )?;
 
     // Write data to transaction account.
-    Transaction::write(transaction, transaction_header, accounts, signers, tx_data)?;
+    let mut transaction_header = *transaction_header;
+    transaction_header.proposal_hash = Transaction::proposal_hash(
+        &transaction_header.program_id,
+        &transaction_header.vaults,
+        &transaction_header.assertions,
+        accounts,
+        tx_data,
+    );
+    transaction_header.proposer = *user.key();
+    transaction_header.rent_payer = *payer.key();
+    let approval_count = if draft { 0 } else { 1 };
+    transaction_header.approvals = u64::to_le_bytes(approval_count);
+    transaction_header.rejections = 0u64.to_le_bytes();
+    if !draft {
+        let threshold = multisig_header.threshold_for(&transaction_header.program_id, tx_data);
+        transaction_header.update_status(threshold)?;
+    }
+    Transaction::write(transaction, &transaction_header, accounts, signers, tx_data)?;
+    MultisigHeader::load_account_mut(multisig)?
+        .0
+        .transaction_index = index.to_le_bytes();
+
+    Event::new(
+        EventKind::CreateTransaction,
+        multisig.key(),
+        transaction.key(),
+        user.key(),
+        approval_count,
+    )
+    .emit();
 
     Ok(())
 }
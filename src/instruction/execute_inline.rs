@@ -0,0 +1,128 @@
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::{
+    error::CustomError,
+    event::{Event, EventKind},
+    state::{Multisig, TransactionAccount, TransactionAssertions, TransactionVaults},
+    utils::{check_invocation, check_transaction_accounts, invoke_transaction},
+};
+
+/// Header of the inline transaction passed to `ExecuteInline` (and of the new
+/// content passed to `UpdateTransaction`), followed by
+/// `num_accounts` [`TransactionAccount`]s and `data_len` bytes of data.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct InlineTransactionHeader {
+    pub program_id: Pubkey,
+    pub num_accounts: [u8; 8],
+    pub data_len: [u8; 8],
+    pub vaults: TransactionVaults,
+    pub assertions: TransactionAssertions,
+}
+
+impl InlineTransactionHeader {
+    /// Parses the header, accounts and data of an inline transaction.
+    pub fn parse(
+        data: &[u8],
+    ) -> Result<(&InlineTransactionHeader, &[TransactionAccount], &[u8]), ProgramError> {
+        let header_size = core::mem::size_of::<InlineTransactionHeader>();
+        let header = bytemuck::try_from_bytes::<InlineTransactionHeader>(
+            data.get(..header_size)
+                .ok_or(ProgramError::InvalidInstructionData)?,
+        )
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        let num_accounts = u64::from_le_bytes(header.num_accounts) as usize;
+        let accounts_size = num_accounts
+            .checked_mul(core::mem::size_of::<TransactionAccount>())
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let data_len = u64::from_le_bytes(header.data_len) as usize;
+        let data_offset = header_size
+            .checked_add(accounts_size)
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        if data.len().checked_sub(data_offset) != Some(data_len) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts =
+            bytemuck::cast_slice::<u8, TransactionAccount>(&data[header_size..data_offset]);
+        Ok((header, accounts, &data[data_offset..]))
+    }
+}
+
+/// Executes an instruction directly through the vault of a 1-of-N multisig,
+/// without creating a transaction account.
+pub fn process_execute_inline(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [user, multisig, program, remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !user.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (header, tx_accounts, tx_data) = InlineTransactionHeader::parse(data)?;
+    let (multisig_header, owners) = Multisig::read(multisig)?;
+
+    // Check if multisig is paused.
+    if multisig_header.is_paused() {
+        return Err(CustomError::MultisigPaused.into());
+    }
+
+    // A single approval must be enough to execute.
+    if multisig_header.threshold_for(&header.program_id, tx_data) != 1 {
+        return Err(CustomError::NotEnoughSigners.into());
+    }
+
+    // Check if user is in the list of multisig owners.
+    if !multisig_header.is_owner(owners, user.key()) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // Check if the user is allowed to execute.
+    if !multisig_header.can_execute(owners, user.key())? {
+        return Err(CustomError::UnauthorizedExecutor.into());
+    }
+
+    // Validate signing vaults.
+    header.vaults.validate(multisig.key())?;
+
+    // Validate assertions.
+    header.assertions.validate(tx_accounts.len())?;
+
+    // Check that the program to invoke is the requested one.
+    if program.key() != &header.program_id {
+        return Err(CustomError::AccountMismatch.into());
+    }
+
+    // Check that the multisig may invoke the program.
+    if !multisig_header.is_program_allowed(&header.program_id)? {
+        return Err(CustomError::ProgramNotAllowed.into());
+    }
+
+    // Check that the instruction does not expose the state of the multisig.
+    check_invocation(multisig.key(), &header.program_id, tx_accounts, tx_data)?;
+    check_transaction_accounts(multisig.key(), tx_accounts, remaining)?;
+
+    // Execute the instruction, signing with the requested vaults, and check
+    // the assertions around it.
+    header.assertions.check(false, tx_accounts, remaining)?;
+    header.vaults.with_signers(multisig.key(), |signers| {
+        invoke_transaction(program, tx_accounts, tx_data, remaining, signers)
+    })?;
+    header.assertions.check(true, tx_accounts, remaining)?;
+
+    Event::new(
+        EventKind::Execute,
+        multisig.key(),
+        &Pubkey::default(),
+        user.key(),
+        1,
+    )
+    .emit();
+
+    Ok(())
+}
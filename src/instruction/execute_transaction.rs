@@ -1,57 +1,98 @@
-use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey, ProgramResult};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
 
 use crate::{
-    constants::{MULTISIG_SEED, TRANSACTION_SEED},
-    state::{Multisig, Transaction},
+    error::CustomError,
+    event::{Event, EventKind},
+    state::{AccountState, Multisig, Transaction, TransactionHeader, TransactionStatus},
+    utils::{check_invocation, check_transaction_accounts, invoke_transaction},
 };
 
 pub fn process_execute_transaction(accounts: &[AccountInfo]) -> ProgramResult {
-    let [transaction, multisig, _system_program] = accounts else {
+    let [executor, transaction, multisig, program, remaining @ ..] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
 
-    let (transaction_header, accounts, signers, tx_data) = Transaction::read(transaction)?;
+    if !executor.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (transaction_header, tx_accounts, _, tx_data) = Transaction::read(transaction)?;
     let (multisig_header, owners) = Multisig::read(multisig)?;
 
-    // Validate multisig account.
-    let multisig_pda = pubkey::create_program_address(
-        &[
-            MULTISIG_SEED.as_bytes(),
-            multisig_header.seed.as_ref(),
-            &[multisig_header.bump as u8],
-        ],
-        &crate::ID,
-    )?;
-    if multisig.key() != &multisig_pda {
-        return Err(ProgramError::InvalidAccountData);
+    // Check if multisig is paused.
+    if multisig_header.is_paused() {
+        return Err(CustomError::MultisigPaused.into());
     }
 
-    // Validate transaction account.
-    let transaction_pda = pubkey::create_program_address(
-        &[
-            TRANSACTION_SEED.as_bytes(),
-            transaction_header.seed.as_ref(),
-            &[transaction_header.bump as u8],
-        ],
-        &crate::ID,
-    )?;
-    if transaction.key() != &transaction_pda {
+    // Check that the transaction belongs to the multisig.
+    if transaction_header.multisig != *multisig.key() {
         return Err(ProgramError::InvalidAccountData);
     }
 
-    // Check if transaction has already been executed.
-    if transaction_header.executed == 255 {
-        return Err(ProgramError::InvalidInstructionData);
+    // Check if the transaction predates a configuration change.
+    if multisig_header.is_stale(&transaction_header.index) {
+        return Err(CustomError::StaleTransaction.into());
+    }
+
+    // Check if the signer is allowed to execute.
+    if !multisig_header.can_execute(owners, executor.key())? {
+        return Err(CustomError::UnauthorizedExecutor.into());
     }
 
-    // Check if we have enough signners.
-    let threshold = u64::from_le_bytes(multisig_header.threshold);
-    let approved_count = signers.iter().filter(|s| s.signed == 255).count() as u64;
+    // Check if we have enough signers.
+    let threshold = multisig_header.threshold_for(&transaction_header.program_id, tx_data);
+    let approved_count = u64::from_le_bytes(transaction_header.approvals);
     if approved_count < threshold {
         return Err(ProgramError::InvalidInstructionData);
     }
 
-    // TODO: Execute the transaction.
+    // Check that the transaction is approved and has not been executed yet.
+    if transaction_header.status()? != TransactionStatus::Approved {
+        return Err(CustomError::InvalidTransactionStatus.into());
+    }
+
+    // Check that the program to invoke is the approved one.
+    if program.key() != &transaction_header.program_id {
+        return Err(CustomError::AccountMismatch.into());
+    }
+
+    // Check that the multisig may invoke the program.
+    if !multisig_header.is_program_allowed(&transaction_header.program_id)? {
+        return Err(CustomError::ProgramNotAllowed.into());
+    }
+
+    // Check that the transaction does not expose the state of the multisig,
+    // including transactions created before these checks existed.
+    check_invocation(
+        multisig.key(),
+        &transaction_header.program_id,
+        tx_accounts,
+        tx_data,
+    )?;
+    check_transaction_accounts(multisig.key(), tx_accounts, remaining)?;
+
+    // Mark the transaction as executed before invoking, so it cannot be
+    // executed again from within the invoked program. The header is copied
+    // first, as the account is written to.
+    let header = *transaction_header;
+    TransactionHeader::load_account_mut(transaction)?.0.status = TransactionStatus::Executed as u8;
+
+    // Execute the transaction, signing with the declared vaults, and check
+    // its assertions around it. A failed assertion aborts the execution.
+    header.assertions.check(false, tx_accounts, remaining)?;
+    header.vaults.with_signers(multisig.key(), |signers| {
+        invoke_transaction(program, tx_accounts, tx_data, remaining, signers)
+    })?;
+    header.assertions.check(true, tx_accounts, remaining)?;
+
+    Event::new(
+        EventKind::Execute,
+        multisig.key(),
+        transaction.key(),
+        executor.key(),
+        approved_count,
+    )
+    .emit();
 
     Ok(())
 }
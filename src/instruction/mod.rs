@@ -1,14 +1,38 @@
 use pinocchio::program_error::ProgramError;
 
+pub mod activate_transaction;
+pub mod approve_and_execute;
 pub mod approve_transaction;
+pub mod approve_with_signatures;
+pub mod cancel_transaction;
 pub mod create_multisig;
 pub mod create_transaction;
+pub mod execute_inline;
 pub mod execute_transaction;
+pub mod pause_multisig;
+pub mod reject_transaction;
+pub mod revoke_vote;
+pub mod set_program_filter;
+pub mod set_threshold_policies;
+pub mod unpause_multisig;
+pub mod update_transaction;
 
+pub use activate_transaction::*;
+pub use approve_and_execute::*;
 pub use approve_transaction::*;
+pub use approve_with_signatures::*;
+pub use cancel_transaction::*;
 pub use create_multisig::*;
 pub use create_transaction::*;
+pub use execute_inline::*;
 pub use execute_transaction::*;
+pub use pause_multisig::*;
+pub use reject_transaction::*;
+pub use revoke_vote::*;
+pub use set_program_filter::*;
+pub use set_threshold_policies::*;
+pub use unpause_multisig::*;
+pub use update_transaction::*;
 
 #[repr(u8)]
 pub enum MultisigInstruction {
@@ -16,6 +40,50 @@ pub enum MultisigInstruction {
     CreateTransaction,
     ApproveTransaction,
     ExecuteTransaction,
+    PauseMultisig,
+    UnpauseMultisig,
+    ApproveAndExecute,
+    ExecuteInline,
+    ApproveWithSignatures,
+    RejectTransaction,
+    RevokeVote,
+    UpdateTransaction,
+    ActivateTransaction,
+    SetThresholdPolicies,
+    SetProgramFilter,
+    CancelTransaction,
+}
+
+impl MultisigInstruction {
+    /// Checks whether the transactions of a multisig may invoke the
+    /// instruction: changes to the configuration of the multisig itself, and
+    /// the actions of a vault as owner of another multisig.
+    pub fn is_invocable(&self) -> bool {
+        self.is_governance()
+            || matches!(
+                self,
+                MultisigInstruction::CreateTransaction
+                    | MultisigInstruction::ApproveTransaction
+                    | MultisigInstruction::ExecuteTransaction
+                    | MultisigInstruction::PauseMultisig
+                    | MultisigInstruction::UnpauseMultisig
+                    | MultisigInstruction::ApproveAndExecute
+                    | MultisigInstruction::RejectTransaction
+                    | MultisigInstruction::RevokeVote
+                    | MultisigInstruction::UpdateTransaction
+                    | MultisigInstruction::ActivateTransaction
+                    | MultisigInstruction::CancelTransaction
+            )
+    }
+
+    /// Checks whether the instruction changes the configuration of a
+    /// multisig, authorized by its own vault.
+    pub fn is_governance(&self) -> bool {
+        matches!(
+            self,
+            MultisigInstruction::SetThresholdPolicies | MultisigInstruction::SetProgramFilter
+        )
+    }
 }
 
 impl TryFrom<&u8> for MultisigInstruction {
@@ -27,6 +95,18 @@ impl TryFrom<&u8> for MultisigInstruction {
             1 => Ok(MultisigInstruction::CreateTransaction),
             2 => Ok(MultisigInstruction::ApproveTransaction),
             3 => Ok(MultisigInstruction::ExecuteTransaction),
+            4 => Ok(MultisigInstruction::PauseMultisig),
+            5 => Ok(MultisigInstruction::UnpauseMultisig),
+            6 => Ok(MultisigInstruction::ApproveAndExecute),
+            7 => Ok(MultisigInstruction::ExecuteInline),
+            8 => Ok(MultisigInstruction::ApproveWithSignatures),
+            9 => Ok(MultisigInstruction::RejectTransaction),
+            10 => Ok(MultisigInstruction::RevokeVote),
+            11 => Ok(MultisigInstruction::UpdateTransaction),
+            12 => Ok(MultisigInstruction::ActivateTransaction),
+            13 => Ok(MultisigInstruction::SetThresholdPolicies),
+            14 => Ok(MultisigInstruction::SetProgramFilter),
+            15 => Ok(MultisigInstruction::CancelTransaction),
             _ => Err(ProgramError::InvalidInstructionData),
         }
     }
@@ -0,0 +1,45 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::{
+    error::CustomError,
+    event::{Event, EventKind},
+    state::{AccountState, Multisig, MultisigHeader},
+};
+
+pub fn process_pause_multisig(accounts: &[AccountInfo]) -> ProgramResult {
+    let [multisig, signers @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let (multisig_header, owners) = Multisig::read(multisig)?;
+
+    // Check if multisig is already paused.
+    if multisig_header.is_paused() {
+        return Err(CustomError::MultisigPaused.into());
+    }
+
+    // Either the guardian or enough owners must have signed.
+    let guardian_signed = multisig_header.guardian != Pubkey::default()
+        && signers
+            .iter()
+            .any(|s| s.key() == &multisig_header.guardian && s.is_signer());
+    let pause_threshold = u64::from_le_bytes(multisig_header.pause_threshold);
+    if !guardian_signed && Multisig::count_signers(owners, signers) < pause_threshold {
+        return Err(CustomError::NotEnoughSigners.into());
+    }
+
+    MultisigHeader::load_account_mut(multisig)?.0.paused = 255;
+
+    Event::new(
+        EventKind::Pause,
+        multisig.key(),
+        &Pubkey::default(),
+        &Pubkey::default(),
+        0,
+    )
+    .emit();
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{
+    error::CustomError,
+    event::{Event, EventKind},
+    state::{Multisig, Transaction},
+};
+
+/// Rejects a transaction. Once enough owners reject it that the threshold can
+/// no longer be met, the transaction is closed to further votes.
+pub fn process_reject_transaction(accounts: &[AccountInfo]) -> ProgramResult {
+    let [user, transaction, multisig] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !user.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (transaction_header, _, _, tx_data) = Transaction::read(transaction)?;
+    let (multisig_header, owners) = Multisig::read(multisig)?;
+
+    // Check if multisig is paused.
+    if multisig_header.is_paused() {
+        return Err(CustomError::MultisigPaused.into());
+    }
+
+    // Check that the transaction belongs to the multisig.
+    if transaction_header.multisig != *multisig.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Check if the transaction predates a configuration change.
+    if multisig_header.is_stale(&transaction_header.index) {
+        return Err(CustomError::StaleTransaction.into());
+    }
+
+    // Check that the transaction is open to votes.
+    if !transaction_header.is_open()? {
+        return Err(CustomError::InvalidTransactionStatus.into());
+    }
+
+    // Check if user is in the list of multisig owners.
+    if !multisig_header.is_owner(owners, user.key()) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let threshold = multisig_header.threshold_for(&transaction_header.program_id, tx_data);
+
+    // Update the rejected status for the user.
+    let (transaction_header, signers) = Transaction::load_signers_mut(transaction)?;
+    let Some(signer_entry) = signers.iter_mut().find(|s| s.pubkey == *user.key()) else {
+        return Err(ProgramError::InvalidInstructionData);
+    };
+    if signer_entry.signed == 255 || signer_entry.rejected == 255 {
+        // User has already voted on the transaction.
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    signer_entry.rejected = 255;
+    transaction_header.add_vote(false, threshold)?;
+
+    Event::new(
+        EventKind::Reject,
+        multisig.key(),
+        transaction.key(),
+        user.key(),
+        u64::from_le_bytes(transaction_header.approvals),
+    )
+    .emit();
+
+    Ok(())
+}
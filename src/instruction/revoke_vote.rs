@@ -0,0 +1,72 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{
+    error::CustomError,
+    event::{Event, EventKind},
+    state::{Multisig, Transaction},
+};
+
+/// Withdraws the approval or rejection of an owner, as long as the
+/// transaction is still open to votes.
+pub fn process_revoke_vote(accounts: &[AccountInfo]) -> ProgramResult {
+    let [user, transaction, multisig] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !user.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (transaction_header, _, _, tx_data) = Transaction::read(transaction)?;
+    let (multisig_header, _) = Multisig::read(multisig)?;
+
+    // Check if multisig is paused.
+    if multisig_header.is_paused() {
+        return Err(CustomError::MultisigPaused.into());
+    }
+
+    // Check that the transaction belongs to the multisig.
+    if transaction_header.multisig != *multisig.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Check if the transaction predates a configuration change.
+    if multisig_header.is_stale(&transaction_header.index) {
+        return Err(CustomError::StaleTransaction.into());
+    }
+
+    // Check that the transaction is open to votes.
+    if !transaction_header.is_open()? {
+        return Err(CustomError::InvalidTransactionStatus.into());
+    }
+
+    let threshold = multisig_header.threshold_for(&transaction_header.program_id, tx_data);
+
+    // Clear the vote of the user.
+    let (transaction_header, signers) = Transaction::load_signers_mut(transaction)?;
+    let Some(signer_entry) = signers.iter_mut().find(|s| s.pubkey == *user.key()) else {
+        return Err(ProgramError::InvalidInstructionData);
+    };
+    let approved = if signer_entry.signed == 255 {
+        signer_entry.signed = 0;
+        true
+    } else if signer_entry.rejected == 255 {
+        signer_entry.rejected = 0;
+        false
+    } else {
+        // User has not voted on the transaction.
+        return Err(ProgramError::InvalidInstructionData);
+    };
+    transaction_header.remove_vote(approved, threshold)?;
+
+    Event::new(
+        EventKind::Revoke,
+        multisig.key(),
+        transaction.key(),
+        user.key(),
+        u64::from_le_bytes(transaction_header.approvals),
+    )
+    .emit();
+
+    Ok(())
+}
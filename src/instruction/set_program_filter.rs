@@ -0,0 +1,52 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::{
+    constants::MAX_FILTERED_PROGRAMS,
+    event::{Event, EventKind},
+    state::{AccountState, Multisig, MultisigHeader},
+};
+
+/// Replaces the program filter of a multisig. The data is the
+/// [`crate::state::ProgramFilter`] followed by the listed programs.
+///
+/// Must be signed by the first vault of the multisig, i.e. executed as an
+/// approved transaction of the multisig itself. Pending transactions are
+/// invalidated, as they may invoke programs that are no longer allowed.
+pub fn process_set_program_filter(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [multisig, vault] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let (program_filter, programs) = data
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let programs = bytemuck::try_cast_slice::<u8, Pubkey>(programs)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    if programs.len() > MAX_FILTERED_PROGRAMS {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // Check that the multisig itself authorized the change.
+    Multisig::check_vault_authority(multisig, vault)?;
+
+    let (multisig_header, _) = MultisigHeader::load_account_mut(multisig)?;
+    multisig_header.program_filter = *program_filter;
+    multisig_header.num_filtered_programs = programs.len() as u8;
+    multisig_header.filtered_programs = [Pubkey::default(); MAX_FILTERED_PROGRAMS];
+    multisig_header.filtered_programs[..programs.len()].copy_from_slice(programs);
+    multisig_header.validate_program_filter()?;
+    multisig_header.invalidate_transactions();
+
+    Event::new(
+        EventKind::SetProgramFilter,
+        multisig.key(),
+        &Pubkey::default(),
+        vault.key(),
+        0,
+    )
+    .emit();
+
+    Ok(())
+}
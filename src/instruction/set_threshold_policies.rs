@@ -0,0 +1,48 @@
+use bytemuck::Zeroable;
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::{
+    constants::MAX_THRESHOLD_POLICIES,
+    event::{Event, EventKind},
+    state::{AccountState, Multisig, MultisigHeader, ThresholdPolicy},
+};
+
+/// Replaces the threshold policies of a multisig.
+///
+/// Must be signed by the first vault of the multisig, i.e. executed as an
+/// approved transaction of the multisig itself. Pending transactions are
+/// invalidated, as the number of approvals they need may have changed.
+pub fn process_set_threshold_policies(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [multisig, vault] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let policies = bytemuck::try_cast_slice::<u8, ThresholdPolicy>(data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    if policies.len() > MAX_THRESHOLD_POLICIES {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // Check that the multisig itself authorized the change.
+    Multisig::check_vault_authority(multisig, vault)?;
+
+    let (multisig_header, _) = MultisigHeader::load_account_mut(multisig)?;
+    multisig_header.num_threshold_policies = policies.len() as u8;
+    multisig_header.threshold_policies = [ThresholdPolicy::zeroed(); MAX_THRESHOLD_POLICIES];
+    multisig_header.threshold_policies[..policies.len()].copy_from_slice(policies);
+    multisig_header.validate_threshold_policies()?;
+    multisig_header.invalidate_transactions();
+
+    Event::new(
+        EventKind::SetThresholdPolicies,
+        multisig.key(),
+        &Pubkey::default(),
+        vault.key(),
+        0,
+    )
+    .emit();
+
+    Ok(())
+}
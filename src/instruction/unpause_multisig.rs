@@ -0,0 +1,41 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::{
+    error::CustomError,
+    event::{Event, EventKind},
+    state::{AccountState, Multisig, MultisigHeader},
+};
+
+pub fn process_unpause_multisig(accounts: &[AccountInfo]) -> ProgramResult {
+    let [multisig, signers @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let (multisig_header, owners) = Multisig::read(multisig)?;
+
+    // Check if multisig is paused.
+    if !multisig_header.is_paused() {
+        return Err(CustomError::MultisigNotPaused.into());
+    }
+
+    // Unpausing requires the full threshold of owners.
+    let threshold = u64::from_le_bytes(multisig_header.threshold);
+    if Multisig::count_signers(owners, signers) < threshold {
+        return Err(CustomError::NotEnoughSigners.into());
+    }
+
+    MultisigHeader::load_account_mut(multisig)?.0.paused = 0;
+
+    Event::new(
+        EventKind::Unpause,
+        multisig.key(),
+        &Pubkey::default(),
+        &Pubkey::default(),
+        0,
+    )
+    .emit();
+
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use bytemuck::Zeroable;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{
+    constants::{MAX_OWNERS, MAX_TRANSACTION_ACCOUNTS},
+    error::CustomError,
+    event::{Event, EventKind},
+    instruction::InlineTransactionHeader,
+    state::{AccountState, Multisig, Transaction, TransactionSigner, TransactionStatus},
+    utils::{check_invocation, check_writable_accounts, resize_account},
+};
+
+/// Replaces the program, signing vaults, accounts and data of a draft
+/// transaction, resizing its account as needed. `data` has the same layout as
+/// `ExecuteInline` data.
+///
+/// The resize is settled with the rent payer of the transaction, passed after
+/// the system program if it is not the proposer. The writable accounts of the
+/// new content follow in order, as for `CreateTransaction`.
+pub fn process_update_transaction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [proposer, transaction, multisig, _system_program, remaining @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if !proposer.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (content, tx_accounts, tx_data) = InlineTransactionHeader::parse(data)?;
+
+    let num_writable = tx_accounts
+        .iter()
+        .filter(|account| account.is_writable != 0)
+        .count();
+    let (rent_payer, writable_accounts) = match remaining.len().checked_sub(num_writable) {
+        Some(0) => (proposer, remaining),
+        Some(1) => (&remaining[0], &remaining[1..]),
+        Some(_) => return Err(ProgramError::InvalidArgument),
+        None => return Err(ProgramError::NotEnoughAccountKeys),
+    };
+
+    let (transaction_header, _, signers, _) = Transaction::read(transaction)?;
+    let (multisig_header, _) = Multisig::read(multisig)?;
+
+    // Check if multisig is paused.
+    if multisig_header.is_paused() {
+        return Err(CustomError::MultisigPaused.into());
+    }
+
+    // Check that the transaction belongs to the multisig.
+    if transaction_header.multisig != *multisig.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Check if the transaction predates a configuration change.
+    if multisig_header.is_stale(&transaction_header.index) {
+        return Err(CustomError::StaleTransaction.into());
+    }
+
+    // Only drafts can be edited, and only by their proposer.
+    if transaction_header.status()? != TransactionStatus::Draft {
+        return Err(CustomError::InvalidTransactionStatus.into());
+    }
+    if transaction_header.proposer != *proposer.key() {
+        return Err(ProgramError::IncorrectAuthority);
+    }
+
+    // Check that the rent is settled with the account that paid it.
+    if transaction_header.rent_payer != *rent_payer.key() {
+        return Err(CustomError::AccountMismatch.into());
+    }
+
+    // Check that the multisig may invoke the program.
+    if !multisig_header.is_program_allowed(&content.program_id)? {
+        return Err(CustomError::ProgramNotAllowed.into());
+    }
+
+    // Check that the transaction does not expose the state of the multisig.
+    check_invocation(multisig.key(), &content.program_id, tx_accounts, tx_data)?;
+    if tx_accounts
+        .iter()
+        .any(|account| account.is_writable != 0 && account.pubkey == *transaction.key())
+    {
+        return Err(CustomError::ProtectedAccount.into());
+    }
+    check_writable_accounts(multisig.key(), tx_accounts, writable_accounts)?;
+
+    // Check that the transaction can be invoked.
+    if tx_accounts.len() > MAX_TRANSACTION_ACCOUNTS {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // Validate signing vaults.
+    content.vaults.validate(multisig.key())?;
+
+    // Validate assertions.
+    content.assertions.validate(tx_accounts.len())?;
+
+    // Keep a copy of the signers, which move with the new content.
+    let mut kept_signers = [TransactionSigner::zeroed(); MAX_OWNERS];
+    let kept_signers = kept_signers
+        .get_mut(..signers.len())
+        .ok_or(ProgramError::InvalidAccountData)?;
+    kept_signers.copy_from_slice(signers);
+
+    let mut header = *transaction_header;
+    header.program_id = content.program_id;
+    header.vaults = content.vaults;
+    header.assertions = content.assertions;
+    header.num_accounts = content.num_accounts;
+    header.data_len = content.data_len;
+    header.proposal_hash = Transaction::proposal_hash(
+        &header.program_id,
+        &header.vaults,
+        &header.assertions,
+        tx_accounts,
+        tx_data,
+    );
+
+    resize_account(rent_payer, transaction, header.account_size()?)?;
+    Transaction::rewrite(transaction, &header, tx_accounts, kept_signers, tx_data)?;
+
+    Event::new(
+        EventKind::Update,
+        multisig.key(),
+        transaction.key(),
+        proposer.key(),
+        0,
+    )
+    .emit();
+
+    Ok(())
+}
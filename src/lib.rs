@@ -2,8 +2,10 @@
 
 pub mod constants;
 pub mod error;
+pub mod event;
 pub mod instruction;
 pub mod state;
+pub mod utils;
 
 #[cfg(feature = "std")]
 extern crate std;
@@ -0,0 +1,347 @@
+use core::fmt;
+
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
+use std::{string::String, vec::Vec};
+
+use super::{AssertionKind, MultisigHeader, Transaction, TransactionStatus};
+use crate::constants::{TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID};
+
+const SYSTEM_PROGRAM_ID: Pubkey = [0; 32];
+
+/// Owned summary of a stored transaction, for reviewing a proposal off-chain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransactionSummary {
+    pub multisig: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub program_id: Pubkey,
+    pub accounts: Vec<AccountSummary>,
+    pub data: Vec<u8>,
+    pub assertions: Vec<AssertionSummary>,
+    pub approvals: Vec<ApprovalSummary>,
+    pub approval_count: u64,
+    pub rejection_count: u64,
+    pub threshold: u64,
+    pub threshold_met: bool,
+    pub status: TransactionStatus,
+    /// Whether the transaction predates the last configuration change.
+    pub stale: bool,
+    pub proposal_hash: [u8; 32],
+    /// The instruction, if it targets a known program.
+    pub instruction: Option<KnownInstruction>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountSummary {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AssertionSummary {
+    pub kind: AssertionKind,
+    pub after: bool,
+    pub account: Pubkey,
+    pub value: [u8; 32],
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApprovalSummary {
+    pub owner: Pubkey,
+    pub approved: bool,
+    pub rejected: bool,
+}
+
+/// Instructions of known programs decoded from a transaction.
+#[derive(Clone, Debug, PartialEq)]
+pub enum KnownInstruction {
+    SystemTransfer {
+        from: Pubkey,
+        to: Pubkey,
+        lamports: u64,
+    },
+    TokenTransfer {
+        source: Pubkey,
+        destination: Pubkey,
+        authority: Pubkey,
+        amount: u64,
+    },
+    TokenTransferChecked {
+        source: Pubkey,
+        mint: Pubkey,
+        destination: Pubkey,
+        authority: Pubkey,
+        amount: u64,
+        decimals: u8,
+    },
+}
+
+impl KnownInstruction {
+    /// Decodes a System transfer or an SPL Token (or Token-2022) transfer.
+    pub fn decode(program_id: &Pubkey, accounts: &[Pubkey], data: &[u8]) -> Option<Self> {
+        match *program_id {
+            SYSTEM_PROGRAM_ID => {
+                let [from, to, ..] = accounts else {
+                    return None;
+                };
+                let (tag, lamports) = data.split_first_chunk::<4>()?;
+                if u32::from_le_bytes(*tag) != 2 || lamports.len() != 8 {
+                    return None;
+                }
+                Some(KnownInstruction::SystemTransfer {
+                    from: *from,
+                    to: *to,
+                    lamports: u64::from_le_bytes(lamports.try_into().ok()?),
+                })
+            }
+            TOKEN_PROGRAM_ID | TOKEN_2022_PROGRAM_ID => match data {
+                [3, amount @ ..] if amount.len() == 8 => {
+                    let [source, destination, authority, ..] = accounts else {
+                        return None;
+                    };
+                    Some(KnownInstruction::TokenTransfer {
+                        source: *source,
+                        destination: *destination,
+                        authority: *authority,
+                        amount: u64::from_le_bytes(amount.try_into().ok()?),
+                    })
+                }
+                [12, amount @ .., decimals] if amount.len() == 8 => {
+                    let [source, mint, destination, authority, ..] = accounts else {
+                        return None;
+                    };
+                    Some(KnownInstruction::TokenTransferChecked {
+                        source: *source,
+                        mint: *mint,
+                        destination: *destination,
+                        authority: *authority,
+                        amount: u64::from_le_bytes(amount.try_into().ok()?),
+                        decimals: *decimals,
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Transaction<'a> {
+    /// Summarizes the data of a transaction account of the multisig with
+    /// header `multisig`.
+    pub fn inspect(
+        data: &[u8],
+        multisig: &MultisigHeader,
+    ) -> Result<TransactionSummary, ProgramError> {
+        let (header, accounts, signers, tx_data) = Transaction::parse(data)?;
+
+        let approval_count = u64::from_le_bytes(header.approvals);
+        let threshold = multisig.threshold_for(&header.program_id, tx_data);
+        let keys: Vec<Pubkey> = accounts.iter().map(|a| a.pubkey).collect();
+
+        Ok(TransactionSummary {
+            multisig: header.multisig,
+            index: u64::from_le_bytes(header.index),
+            proposer: header.proposer,
+            program_id: header.program_id,
+            accounts: accounts
+                .iter()
+                .map(|a| AccountSummary {
+                    pubkey: a.pubkey,
+                    is_signer: a.is_signer != 0,
+                    is_writable: a.is_writable != 0,
+                })
+                .collect(),
+            data: tx_data.to_vec(),
+            assertions: header
+                .assertions
+                .assertions()
+                .iter()
+                .map(|a| {
+                    Ok(AssertionSummary {
+                        kind: a.kind()?,
+                        after: a.after == 255,
+                        account: accounts
+                            .get(a.account_index as usize)
+                            .ok_or(ProgramError::InvalidAccountData)?
+                            .pubkey,
+                        value: a.value,
+                    })
+                })
+                .collect::<Result<_, ProgramError>>()?,
+            approvals: signers
+                .iter()
+                .map(|s| ApprovalSummary {
+                    owner: s.pubkey,
+                    approved: s.signed == 255,
+                    rejected: s.rejected == 255,
+                })
+                .collect(),
+            approval_count,
+            rejection_count: u64::from_le_bytes(header.rejections),
+            threshold,
+            threshold_met: approval_count >= threshold,
+            status: header.status()?,
+            stale: multisig.is_stale(&header.index),
+            proposal_hash: header.proposal_hash,
+            instruction: KnownInstruction::decode(&header.program_id, &keys, tx_data),
+        })
+    }
+}
+
+fn encode(key: &[u8]) -> String {
+    bs58::encode(key).into_string()
+}
+
+impl fmt::Display for KnownInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KnownInstruction::SystemTransfer { from, to, lamports } => write!(
+                f,
+                "System transfer of {} lamports from {} to {}",
+                lamports,
+                encode(from),
+                encode(to)
+            ),
+            KnownInstruction::TokenTransfer {
+                source,
+                destination,
+                authority,
+                amount,
+            } => write!(
+                f,
+                "Token transfer of {} from {} to {} (authority {})",
+                amount,
+                encode(source),
+                encode(destination),
+                encode(authority)
+            ),
+            KnownInstruction::TokenTransferChecked {
+                source,
+                mint,
+                destination,
+                authority,
+                amount,
+                decimals,
+            } => write!(
+                f,
+                "Token transfer of {} (mint {}, {} decimals) from {} to {} (authority {})",
+                amount,
+                encode(mint),
+                decimals,
+                encode(source),
+                encode(destination),
+                encode(authority)
+            ),
+        }
+    }
+}
+
+impl fmt::Display for AssertionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut amount = [0; 8];
+        amount.copy_from_slice(&self.value[..8]);
+        let amount = u64::from_le_bytes(amount);
+        write!(
+            f,
+            "{} execution: ",
+            if self.after { "after" } else { "before" }
+        )?;
+        match self.kind {
+            AssertionKind::LamportsAtLeast => {
+                write!(
+                    f,
+                    "{} holds at least {} lamports",
+                    encode(&self.account),
+                    amount
+                )
+            }
+            AssertionKind::LamportsAtMost => {
+                write!(
+                    f,
+                    "{} holds at most {} lamports",
+                    encode(&self.account),
+                    amount
+                )
+            }
+            AssertionKind::TokenAmountAtLeast => {
+                write!(
+                    f,
+                    "{} holds at least {} tokens",
+                    encode(&self.account),
+                    amount
+                )
+            }
+            AssertionKind::TokenAmountAtMost => {
+                write!(
+                    f,
+                    "{} holds at most {} tokens",
+                    encode(&self.account),
+                    amount
+                )
+            }
+            AssertionKind::DataHashEquals => write!(
+                f,
+                "data of {} hashes to {}",
+                encode(&self.account),
+                encode(&self.value)
+            ),
+        }
+    }
+}
+
+impl fmt::Display for TransactionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Multisig:  {}", encode(&self.multisig))?;
+        writeln!(f, "Index:     {}", self.index)?;
+        writeln!(f, "Proposer:  {}", encode(&self.proposer))?;
+        writeln!(f, "Program:   {}", encode(&self.program_id))?;
+        writeln!(f, "Accounts:")?;
+        for account in &self.accounts {
+            writeln!(
+                f,
+                "  {} {}{}",
+                encode(&account.pubkey),
+                if account.is_signer { "s" } else { "-" },
+                if account.is_writable { "w" } else { "-" }
+            )?;
+        }
+        writeln!(f, "Data:      {}", encode(&self.data))?;
+        if let Some(instruction) = &self.instruction {
+            writeln!(f, "Decoded:   {}", instruction)?;
+        }
+        if !self.assertions.is_empty() {
+            writeln!(f, "Assertions:")?;
+        }
+        for assertion in &self.assertions {
+            writeln!(f, "  {}", assertion)?;
+        }
+        writeln!(f, "Approvals: {}/{}", self.approval_count, self.threshold)?;
+        writeln!(f, "Rejections: {}", self.rejection_count)?;
+        for approval in &self.approvals {
+            writeln!(
+                f,
+                "  {} {}",
+                encode(&approval.owner),
+                if approval.approved {
+                    "approved"
+                } else if approval.rejected {
+                    "rejected"
+                } else {
+                    "pending"
+                }
+            )?;
+        }
+        let status = match self.status {
+            TransactionStatus::Active | TransactionStatus::Approved if self.stale => "stale",
+            TransactionStatus::Draft => "draft",
+            TransactionStatus::Active => "awaiting approvals",
+            TransactionStatus::Approved => "ready to execute",
+            TransactionStatus::Rejected => "rejected",
+            TransactionStatus::Executed => "executed",
+            TransactionStatus::Cancelled => "cancelled",
+        };
+        write!(f, "Status:    {}", status)
+    }
+}
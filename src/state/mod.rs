@@ -1,5 +1,111 @@
+#[cfg(feature = "std")]
+pub mod inspect;
 pub mod multisig;
 pub mod transaction;
 
+#[cfg(feature = "std")]
+pub use inspect::*;
 pub use multisig::*;
 pub use transaction::*;
+
+use bytemuck::Pod;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError};
+
+/// Layout shared by program accounts: a fixed-size header whose first byte
+/// is the account type discriminator, followed by a body whose size the
+/// header describes.
+///
+/// Accounts are loaded in place. Loading an account checks that it is owned
+/// by the program, that it carries the discriminator of the type and that
+/// its data is large enough for the body.
+pub trait AccountState: Pod {
+    /// Account type, stored in the first byte of the header.
+    const DISCRIMINATOR: u8;
+
+    /// Size of the body following the header.
+    fn body_size(&self) -> Result<usize, ProgramError>;
+
+    /// Size of an account holding this header and its body.
+    fn account_size(&self) -> Result<usize, ProgramError> {
+        core::mem::size_of::<Self>()
+            .checked_add(self.body_size()?)
+            .ok_or(ProgramError::InvalidAccountData)
+    }
+
+    /// Returns the header and body stored in `data`.
+    fn load(data: &[u8]) -> Result<(&Self, &[u8]), ProgramError> {
+        if data.len() < core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (header, body) = data.split_at(core::mem::size_of::<Self>());
+        let header = bytemuck::try_from_bytes::<Self>(header)
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        if header.discriminator() != Self::DISCRIMINATOR {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = body
+            .get(..header.body_size()?)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        Ok((header, body))
+    }
+
+    /// Returns the header and body stored in `data`, mutably.
+    fn load_mut(data: &mut [u8]) -> Result<(&mut Self, &mut [u8]), ProgramError> {
+        if data.len() < core::mem::size_of::<Self>() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (header, body) = data.split_at_mut(core::mem::size_of::<Self>());
+        let header = bytemuck::try_from_bytes_mut::<Self>(header)
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        if header.discriminator() != Self::DISCRIMINATOR {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let body = body
+            .get_mut(..header.body_size()?)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        Ok((header, body))
+    }
+
+    /// Loads the header and body of a program account.
+    fn load_account(account: &AccountInfo) -> Result<(&Self, &[u8]), ProgramError> {
+        if !account.is_owned_by(&crate::ID) {
+            return Err(ProgramError::IllegalOwner);
+        }
+        Self::load(unsafe { account.borrow_data_unchecked() })
+    }
+
+    /// Loads the header and body of a program account, mutably.
+    #[allow(clippy::mut_from_ref)]
+    fn load_account_mut(account: &AccountInfo) -> Result<(&mut Self, &mut [u8]), ProgramError> {
+        if !account.is_owned_by(&crate::ID) {
+            return Err(ProgramError::IllegalOwner);
+        }
+        Self::load_mut(unsafe { account.borrow_mut_data_unchecked() })
+    }
+
+    /// Writes `header`, with the discriminator of the type, to a newly
+    /// created program account and returns its body for the caller to fill.
+    #[allow(clippy::mut_from_ref)]
+    fn init<'a>(account: &'a AccountInfo, header: &Self) -> Result<&'a mut [u8], ProgramError> {
+        if !account.is_owned_by(&crate::ID) {
+            return Err(ProgramError::IllegalOwner);
+        }
+        let data = unsafe { account.borrow_mut_data_unchecked() };
+        if data.first() != Some(&0) {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let header_bytes = bytemuck::bytes_of(header);
+        data.get_mut(..header_bytes.len())
+            .ok_or(ProgramError::AccountDataTooSmall)?
+            .copy_from_slice(header_bytes);
+        data[0] = Self::DISCRIMINATOR;
+
+        Self::load_mut(data).map(|(_, body)| body)
+    }
+
+    /// Returns the discriminator stored in the header.
+    fn discriminator(&self) -> u8 {
+        bytemuck::bytes_of(self)[0]
+    }
+}
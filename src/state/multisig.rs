@@ -1,14 +1,129 @@
 use bytemuck::{Pod, Zeroable};
-use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use super::AccountState;
+use crate::constants::{
+    MAX_EXECUTORS, MAX_FILTERED_PROGRAMS, MAX_OWNERS, MAX_POLICY_PREFIX_LEN,
+    MAX_THRESHOLD_POLICIES, VAULT_SEED,
+};
+use crate::utils::find_program_address;
+
+/// Who may trigger the execution of an approved transaction.
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq)]
+pub enum ExecutePolicy {
+    /// Any owner of the multisig.
+    AnyMember,
+    /// Only the designated executors.
+    Executors,
+    /// Anyone.
+    Permissionless,
+}
+
+impl TryFrom<u8> for ExecutePolicy {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ExecutePolicy::AnyMember),
+            1 => Ok(ExecutePolicy::Executors),
+            2 => Ok(ExecutePolicy::Permissionless),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+/// Which programs the transactions of a multisig may invoke.
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq)]
+pub enum ProgramFilter {
+    /// Any program.
+    None,
+    /// Only the listed programs.
+    Allowlist,
+    /// Any program but the listed ones.
+    Denylist,
+}
+
+impl TryFrom<u8> for ProgramFilter {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ProgramFilter::None),
+            1 => Ok(ProgramFilter::Allowlist),
+            2 => Ok(ProgramFilter::Denylist),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+/// Threshold of the transactions invoking `program_id` with data starting
+/// with `prefix`.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct ThresholdPolicy {
+    pub program_id: Pubkey,
+    /// Number of bytes of `prefix` to match, 0 to match any data.
+    pub prefix_len: u8,
+    pub prefix: [u8; MAX_POLICY_PREFIX_LEN],
+    pub threshold: [u8; 8],
+}
+
+impl ThresholdPolicy {
+    pub fn prefix(&self) -> &[u8] {
+        &self.prefix[..(self.prefix_len as usize).min(MAX_POLICY_PREFIX_LEN)]
+    }
+
+    /// Checks whether the policy applies to an instruction of `program_id`
+    /// with `data`.
+    pub fn matches(&self, program_id: &Pubkey, data: &[u8]) -> bool {
+        self.program_id == *program_id && data.starts_with(self.prefix())
+    }
+}
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
 pub struct MultisigHeader {
+    /// Account type, always [`MultisigHeader::DISCRIMINATOR`].
+    pub discriminator: u8,
     pub seed: [u8; 8],
     pub num_owners: [u8; 8],
     pub threshold: [u8; 8],
     pub nonce: u8,
     pub bump: u8,
+    /// Optional key allowed to pause the multisig on its own (all zeros if unset).
+    pub guardian: Pubkey,
+    /// Number of owners required to pause the multisig.
+    pub pause_threshold: [u8; 8],
+    /// Set to 255 while the multisig is paused.
+    pub paused: u8,
+    /// [`ExecutePolicy`] of the multisig.
+    pub execute_policy: u8,
+    /// Number of entries used in `executors`.
+    pub num_executors: u8,
+    /// Designated executors, used with [`ExecutePolicy::Executors`].
+    pub executors: [Pubkey; MAX_EXECUTORS],
+    /// Index of the last transaction created.
+    pub transaction_index: [u8; 8],
+    /// Transactions up to this index predate the last configuration change
+    /// and can no longer be approved or executed.
+    pub stale_transaction_index: [u8; 8],
+    /// Set to 255 if owners are stored in strictly ascending order, in which
+    /// case membership is checked with a binary search.
+    pub sorted_owners: u8,
+    /// Number of entries used in `threshold_policies`.
+    pub num_threshold_policies: u8,
+    /// Thresholds overriding `threshold` for specific programs or instructions.
+    pub threshold_policies: [ThresholdPolicy; MAX_THRESHOLD_POLICIES],
+    /// [`ProgramFilter`] of the multisig.
+    pub program_filter: u8,
+    /// Number of entries used in `filtered_programs`.
+    pub num_filtered_programs: u8,
+    /// Programs allowed or denied by `program_filter`.
+    pub filtered_programs: [Pubkey; MAX_FILTERED_PROGRAMS],
 }
 
 pub struct Multisig<'a> {
@@ -17,41 +132,184 @@ pub struct Multisig<'a> {
 }
 
 impl<'a> Multisig<'a> {
+    /// Reads a multisig account. The account is authenticated by program
+    /// ownership and its discriminator, since only `CreateMultisig` writes one.
     pub fn read(account: &AccountInfo) -> Result<(&MultisigHeader, &[Pubkey]), ProgramError> {
-        let data = unsafe { account.borrow_data_unchecked() };
-        Multisig::parse(data)
+        let (header, owners) = MultisigHeader::load_account(account)?;
+        Ok((header, bytemuck::cast_slice::<u8, Pubkey>(owners)))
     }
 
+    /// Initializes a newly created multisig account.
     pub fn write(
         account: &AccountInfo,
         header: &MultisigHeader,
         owners: &[Pubkey],
     ) -> Result<(), ProgramError> {
-        let data = unsafe { account.borrow_mut_data_unchecked() };
-        let header_bytes = bytemuck::bytes_of(header);
-        let owners_bytes = bytemuck::cast_slice::<Pubkey, u8>(owners);
+        MultisigHeader::init(account, header)?
+            .copy_from_slice(bytemuck::cast_slice::<Pubkey, u8>(owners));
+        Ok(())
+    }
+
+    pub fn parse(data: &[u8]) -> Result<(&MultisigHeader, &[Pubkey]), ProgramError> {
+        let (header, owners) = MultisigHeader::load(data)?;
+        Ok((header, bytemuck::cast_slice::<u8, Pubkey>(owners)))
+    }
+
+    /// Returns the seeds of vault `index` of `multisig`, without bump.
+    pub fn vault_seeds<'b>(multisig: &'b Pubkey, index: &'b [u8; 1]) -> [&'b [u8]; 3] {
+        [VAULT_SEED.as_bytes(), multisig.as_ref(), index]
+    }
 
-        data[..header_bytes.len()].copy_from_slice(header_bytes);
-        data[header_bytes.len()..header_bytes.len() + owners_bytes.len()]
-            .copy_from_slice(owners_bytes);
+    /// Derives the address and bump of vault `index` of `multisig`, on-chain
+    /// or off-chain.
+    pub fn vault_address(multisig: &Pubkey, index: u8) -> Result<(Pubkey, u8), ProgramError> {
+        find_program_address(&Multisig::vault_seeds(multisig, &[index]), &crate::ID)
+            .ok_or(ProgramError::InvalidSeeds)
+    }
 
+    /// Checks that `authority` is the first vault of `multisig` and signed,
+    /// i.e. that the instruction was approved and executed by the multisig.
+    pub fn check_vault_authority(multisig: &AccountInfo, authority: &AccountInfo) -> ProgramResult {
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if *authority.key() != Multisig::vault_address(multisig.key(), 0)?.0 {
+            return Err(ProgramError::IncorrectAuthority);
+        }
         Ok(())
     }
 
-    pub fn parse(data: &[u8]) -> Result<(&MultisigHeader, &[Pubkey]), ProgramError> {
-        let header_size = core::mem::size_of::<MultisigHeader>();
-        let header = bytemuck::try_from_bytes::<MultisigHeader>(&data[..header_size])
-            .map_err(|_| ProgramError::InvalidAccountData)?;
-        let num_owners = u64::from_le_bytes(header.num_owners);
-        let owners_data = &data[header_size..];
-        let owners = bytemuck::cast_slice::<u8, Pubkey>(owners_data);
-        Ok((header, &owners[..num_owners as usize]))
+    /// Returns the number of distinct owners that signed the instruction.
+    pub fn count_signers(owners: &[Pubkey], accounts: &[AccountInfo]) -> u64 {
+        owners
+            .iter()
+            .filter(|owner| accounts.iter().any(|a| a.key() == *owner && a.is_signer()))
+            .count() as u64
+    }
+}
+
+impl AccountState for MultisigHeader {
+    const DISCRIMINATOR: u8 = 1;
+
+    fn body_size(&self) -> Result<usize, ProgramError> {
+        let num_owners = u64::from_le_bytes(self.num_owners);
+        if num_owners > MAX_OWNERS as u64 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(num_owners as usize * core::mem::size_of::<Pubkey>())
+    }
+}
+
+impl MultisigHeader {
+    pub fn is_paused(&self) -> bool {
+        self.paused == 255
+    }
+
+    /// Checks whether the transaction with the given index is stale.
+    pub fn is_stale(&self, index: &[u8; 8]) -> bool {
+        u64::from_le_bytes(*index) <= u64::from_le_bytes(self.stale_transaction_index)
+    }
+
+    /// Marks all existing transactions as stale. Called on every change to
+    /// the configuration of the multisig.
+    pub fn invalidate_transactions(&mut self) {
+        self.stale_transaction_index = self.transaction_index;
+    }
+
+    /// Checks whether `key` is one of `owners`.
+    pub fn is_owner(&self, owners: &[Pubkey], key: &Pubkey) -> bool {
+        if self.sorted_owners == 255 {
+            owners.binary_search(key).is_ok()
+        } else {
+            owners.contains(key)
+        }
     }
 
-    pub fn size(num_owners: u64) -> usize {
-        let header_size = core::mem::size_of::<MultisigHeader>();
-        let pubkeys_size = num_owners as usize * core::mem::size_of::<Pubkey>();
+    pub fn executors(&self) -> &[Pubkey] {
+        &self.executors[..(self.num_executors as usize).min(MAX_EXECUTORS)]
+    }
+
+    pub fn threshold_policies(&self) -> &[ThresholdPolicy] {
+        &self.threshold_policies
+            [..(self.num_threshold_policies as usize).min(MAX_THRESHOLD_POLICIES)]
+    }
+
+    /// Returns the number of approvals required to execute an instruction of
+    /// `program_id` with `data`: the threshold of the matching policy with the
+    /// longest prefix, or the threshold of the multisig if none matches.
+    ///
+    /// Instructions of this program always require the strictest threshold:
+    /// they can change the policies of this multisig, or act on another
+    /// multisig the vault belongs to, neither of which a policy scoped to a
+    /// program can vouch for.
+    pub fn threshold_for(&self, program_id: &Pubkey, data: &[u8]) -> u64 {
+        let threshold = u64::from_le_bytes(self.threshold);
+        let policies = self.threshold_policies().iter();
+        if program_id == &crate::ID {
+            return policies
+                .map(|policy| u64::from_le_bytes(policy.threshold))
+                .fold(threshold, u64::max);
+        }
+        policies
+            .filter(|policy| policy.matches(program_id, data))
+            .max_by_key(|policy| policy.prefix_len)
+            .map_or(threshold, |policy| u64::from_le_bytes(policy.threshold))
+    }
+
+    /// Checks that the threshold policies can be met by the owners.
+    pub fn validate_threshold_policies(&self) -> ProgramResult {
+        if self.num_threshold_policies as usize > MAX_THRESHOLD_POLICIES {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let num_owners = u64::from_le_bytes(self.num_owners);
+        for policy in self.threshold_policies() {
+            let threshold = u64::from_le_bytes(policy.threshold);
+            if policy.prefix_len as usize > MAX_POLICY_PREFIX_LEN
+                || !(threshold > 0 && threshold <= num_owners)
+            {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn filtered_programs(&self) -> &[Pubkey] {
+        &self.filtered_programs[..(self.num_filtered_programs as usize).min(MAX_FILTERED_PROGRAMS)]
+    }
+
+    /// Checks whether transactions of the multisig may invoke `program_id`.
+    ///
+    /// Instructions of this program are always allowed, so that the filter
+    /// itself can still be changed. They cannot lend a vault to a program the
+    /// filter excludes: see [`crate::utils::check_invocation`].
+    pub fn is_program_allowed(&self, program_id: &Pubkey) -> Result<bool, ProgramError> {
+        if program_id == &crate::ID {
+            return Ok(true);
+        }
+        let listed = self.filtered_programs().contains(program_id);
+        Ok(match ProgramFilter::try_from(self.program_filter)? {
+            ProgramFilter::None => true,
+            ProgramFilter::Allowlist => listed,
+            ProgramFilter::Denylist => !listed,
+        })
+    }
+
+    /// Checks that the program filter is well formed.
+    pub fn validate_program_filter(&self) -> ProgramResult {
+        ProgramFilter::try_from(self.program_filter)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+        if self.num_filtered_programs as usize > MAX_FILTERED_PROGRAMS {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(())
+    }
 
-        header_size + pubkeys_size
+    /// Checks whether `executor` may execute transactions of the multisig.
+    pub fn can_execute(&self, owners: &[Pubkey], executor: &Pubkey) -> Result<bool, ProgramError> {
+        Ok(match ExecutePolicy::try_from(self.execute_policy)? {
+            ExecutePolicy::AnyMember => self.is_owner(owners, executor),
+            ExecutePolicy::Executors => self.executors().contains(executor),
+            ExecutePolicy::Permissionless => true,
+        })
     }
 }
@@ -1,17 +1,278 @@
 use bytemuck::{Pod, Zeroable};
-use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    seeds, ProgramResult,
+};
+
+use super::{AccountState, Multisig};
+use crate::{
+    constants::{
+        APPROVAL_DOMAIN, MAX_ASSERTIONS, MAX_SIGNING_VAULTS, TOKEN_2022_PROGRAM_ID,
+        TOKEN_PROGRAM_ID,
+    },
+    error::CustomError,
+    utils::hash,
+};
+
+/// Vaults of the multisig that sign when a transaction is executed.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct TransactionVaults {
+    pub num_vaults: u8,
+    pub indices: [u8; MAX_SIGNING_VAULTS],
+    pub bumps: [u8; MAX_SIGNING_VAULTS],
+}
+
+impl TransactionVaults {
+    fn len(&self) -> usize {
+        (self.num_vaults as usize).min(MAX_SIGNING_VAULTS)
+    }
+
+    /// Checks that every declared vault is a PDA of `multisig` with its
+    /// canonical bump.
+    pub fn validate(&self, multisig: &Pubkey) -> ProgramResult {
+        if self.num_vaults as usize > MAX_SIGNING_VAULTS {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        for (index, bump) in self.indices.iter().zip(self.bumps).take(self.len()) {
+            let (_, canonical_bump) = Multisig::vault_address(multisig, *index)?;
+            if bump != canonical_bump {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+        }
+        Ok(())
+    }
+
+    /// Calls `f` with the signers of the declared vaults of `multisig`.
+    pub fn with_signers<R>(&self, multisig: &Pubkey, f: impl FnOnce(&[Signer]) -> R) -> R {
+        let indices = self.indices.map(|index| [index]);
+        let bumps = self.bumps.map(|bump| [bump]);
+        let seeds: [[Seed; 4]; MAX_SIGNING_VAULTS] = core::array::from_fn(|i| {
+            let [prefix, multisig, index] = Multisig::vault_seeds(multisig, &indices[i]);
+            seeds!(prefix, multisig, index, &bumps[i])
+        });
+        let signers: [Signer; MAX_SIGNING_VAULTS] =
+            core::array::from_fn(|i| Signer::from(&seeds[i]));
+        f(&signers[..self.len()])
+    }
+}
+
+/// Condition checked by an assertion on an account of the transaction.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AssertionKind {
+    /// The account holds at least `value` lamports.
+    LamportsAtLeast,
+    /// The account holds at most `value` lamports.
+    LamportsAtMost,
+    /// The account is a token account holding at least `value` tokens.
+    TokenAmountAtLeast,
+    /// The account is a token account holding at most `value` tokens.
+    TokenAmountAtMost,
+    /// The SHA-256 hash of the account data is `value`.
+    DataHashEquals,
+}
+
+impl TryFrom<u8> for AssertionKind {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(AssertionKind::LamportsAtLeast),
+            1 => Ok(AssertionKind::LamportsAtMost),
+            2 => Ok(AssertionKind::TokenAmountAtLeast),
+            3 => Ok(AssertionKind::TokenAmountAtMost),
+            4 => Ok(AssertionKind::DataHashEquals),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+/// Size of an SPL token account, and offset of its amount.
+const TOKEN_ACCOUNT_LEN: usize = 165;
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+
+/// Condition on an account of a transaction, checked before or after the
+/// transaction is executed.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct TransactionAssertion {
+    /// [`AssertionKind`] of the assertion.
+    pub kind: u8,
+    /// Set to 255 if checked after execution, before otherwise.
+    pub after: u8,
+    /// Index of the account in the accounts of the transaction.
+    pub account_index: u8,
+    /// Amount, as a little-endian `u64` in the first 8 bytes, or data hash.
+    pub value: [u8; 32],
+}
+
+impl TransactionAssertion {
+    pub fn kind(&self) -> Result<AssertionKind, ProgramError> {
+        AssertionKind::try_from(self.kind)
+    }
+
+    /// Amount compared by lamport and token amount assertions.
+    pub fn amount(&self) -> u64 {
+        let mut amount = [0; 8];
+        amount.copy_from_slice(&self.value[..8]);
+        u64::from_le_bytes(amount)
+    }
+
+    /// Checks whether the assertion holds for `account`.
+    pub fn holds(&self, account: &AccountInfo) -> Result<bool, ProgramError> {
+        Ok(match self.kind()? {
+            AssertionKind::LamportsAtLeast => account.lamports() >= self.amount(),
+            AssertionKind::LamportsAtMost => account.lamports() <= self.amount(),
+            AssertionKind::TokenAmountAtLeast => token_amount(account)? >= self.amount(),
+            AssertionKind::TokenAmountAtMost => token_amount(account)? <= self.amount(),
+            AssertionKind::DataHashEquals => {
+                hash(&[unsafe { account.borrow_data_unchecked() }]) == self.value
+            }
+        })
+    }
+}
+
+/// Reads the amount of an SPL token account.
+fn token_amount(account: &AccountInfo) -> Result<u64, ProgramError> {
+    if !account.is_owned_by(&TOKEN_PROGRAM_ID) && !account.is_owned_by(&TOKEN_2022_PROGRAM_ID) {
+        return Err(CustomError::AssertionFailed.into());
+    }
+    let data = unsafe { account.borrow_data_unchecked() };
+    if data.len() < TOKEN_ACCOUNT_LEN {
+        return Err(CustomError::AssertionFailed.into());
+    }
+    let mut amount = [0; 8];
+    amount.copy_from_slice(&data[TOKEN_ACCOUNT_AMOUNT_OFFSET..TOKEN_ACCOUNT_AMOUNT_OFFSET + 8]);
+    Ok(u64::from_le_bytes(amount))
+}
+
+/// Assertions a transaction carries, checked around its execution.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct TransactionAssertions {
+    pub num_assertions: u8,
+    pub assertions: [TransactionAssertion; MAX_ASSERTIONS],
+}
+
+impl TransactionAssertions {
+    pub fn assertions(&self) -> &[TransactionAssertion] {
+        &self.assertions[..(self.num_assertions as usize).min(MAX_ASSERTIONS)]
+    }
+
+    /// Checks that every assertion is well formed and refers to one of the
+    /// `num_accounts` accounts of the transaction.
+    pub fn validate(&self, num_accounts: usize) -> ProgramResult {
+        if self.num_assertions as usize > MAX_ASSERTIONS {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        for assertion in self.assertions() {
+            assertion
+                .kind()
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            if assertion.account_index as usize >= num_accounts {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the assertions to evaluate before (or `after`) execution
+    /// against `account_infos`, passed in the order of `accounts`.
+    pub fn check(
+        &self,
+        after: bool,
+        accounts: &[TransactionAccount],
+        account_infos: &[AccountInfo],
+    ) -> ProgramResult {
+        for assertion in self.assertions() {
+            if (assertion.after == 255) != after {
+                continue;
+            }
+            let index = assertion.account_index as usize;
+            let (Some(account), Some(info)) = (accounts.get(index), account_infos.get(index))
+            else {
+                return Err(CustomError::AccountMismatch.into());
+            };
+            if info.key() != &account.pubkey {
+                return Err(CustomError::AccountMismatch.into());
+            }
+            if !assertion.holds(info)? {
+                return Err(CustomError::AssertionFailed.into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Lifecycle of a transaction.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransactionStatus {
+    /// Being written by its proposer, not yet open to votes.
+    Draft,
+    /// Open to votes.
+    Active,
+    /// Approved by the threshold of owners and ready to execute.
+    Approved,
+    /// Rejected by enough owners that the threshold can no longer be met.
+    Rejected,
+    /// Executed.
+    Executed,
+    /// Withdrawn before execution.
+    Cancelled,
+}
+
+impl TryFrom<u8> for TransactionStatus {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(TransactionStatus::Draft),
+            1 => Ok(TransactionStatus::Active),
+            2 => Ok(TransactionStatus::Approved),
+            3 => Ok(TransactionStatus::Rejected),
+            4 => Ok(TransactionStatus::Executed),
+            5 => Ok(TransactionStatus::Cancelled),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
 pub struct TransactionHeader {
+    /// Account type, always [`TransactionHeader::DISCRIMINATOR`].
+    pub discriminator: u8,
     pub multisig: Pubkey,
+    /// Owner that created the transaction, the only one allowed to edit it
+    /// while it is a draft.
+    pub proposer: Pubkey,
+    /// Account that paid the rent of the transaction, which funds and is
+    /// refunded for any resize of its account.
+    pub rent_payer: Pubkey,
     pub program_id: Pubkey,
     pub num_accounts: [u8; 8],
     pub num_signers: [u8; 8],
     pub data_len: [u8; 8],
-    pub executed: u8,
-    pub seed: [u8; 8],
+    /// [`TransactionStatus`] of the transaction.
+    pub status: u8,
+    /// Number of owners that approved the transaction.
+    pub approvals: [u8; 8],
+    /// Number of owners that rejected the transaction.
+    pub rejections: [u8; 8],
+    /// Sequential index of the transaction within its multisig.
+    pub index: [u8; 8],
     pub bump: u8,
+    /// [`Transaction::proposal_hash`] of the transaction, set on creation.
+    pub proposal_hash: [u8; 32],
+    /// Vaults that sign the transaction on execution.
+    pub vaults: TransactionVaults,
+    /// Assertions checked around the execution of the transaction.
+    pub assertions: TransactionAssertions,
 }
 
 #[repr(C)]
@@ -26,9 +287,20 @@ pub struct TransactionAccount {
 #[derive(Clone, Copy, Pod, Zeroable)]
 pub struct TransactionSigner {
     pub pubkey: Pubkey,
+    /// Set to 255 if the owner approved the transaction.
     pub signed: u8,
+    /// Set to 255 if the owner rejected the transaction.
+    pub rejected: u8,
 }
 
+/// Borrowed view of a transaction: header, accounts, signers and instruction data.
+pub type TransactionParts<'a> = (
+    &'a TransactionHeader,
+    &'a [TransactionAccount],
+    &'a [TransactionSigner],
+    &'a [u8],
+);
+
 pub struct Transaction<'a> {
     pub header: TransactionHeader,
     pub accounts: &'a [TransactionAccount],
@@ -36,107 +308,208 @@ pub struct Transaction<'a> {
     pub data: &'a [u8],
 }
 
+impl AccountState for TransactionHeader {
+    const DISCRIMINATOR: u8 = 2;
+
+    fn body_size(&self) -> Result<usize, ProgramError> {
+        Ok(self.body_offsets()?.2)
+    }
+}
+
+impl TransactionHeader {
+    pub fn status(&self) -> Result<TransactionStatus, ProgramError> {
+        TransactionStatus::try_from(self.status)
+    }
+
+    /// Checks whether the transaction accepts votes.
+    pub fn is_open(&self) -> Result<bool, ProgramError> {
+        Ok(matches!(
+            self.status()?,
+            TransactionStatus::Active | TransactionStatus::Approved
+        ))
+    }
+
+    /// Records a vote of an owner and updates the status of the transaction.
+    pub fn add_vote(&mut self, approve: bool, threshold: u64) -> ProgramResult {
+        let counter = if approve {
+            &mut self.approvals
+        } else {
+            &mut self.rejections
+        };
+        *counter = u64::from_le_bytes(*counter)
+            .checked_add(1)
+            .ok_or(CustomError::IncrementError)?
+            .to_le_bytes();
+        self.update_status(threshold)
+    }
+
+    /// Withdraws a vote of an owner and updates the status of the transaction.
+    pub fn remove_vote(&mut self, approve: bool, threshold: u64) -> ProgramResult {
+        let counter = if approve {
+            &mut self.approvals
+        } else {
+            &mut self.rejections
+        };
+        *counter = u64::from_le_bytes(*counter)
+            .checked_sub(1)
+            .ok_or(CustomError::DecrementError)?
+            .to_le_bytes();
+        self.update_status(threshold)
+    }
+
+    /// Derives the status of an open transaction from its vote counters.
+    pub fn update_status(&mut self, threshold: u64) -> ProgramResult {
+        let approvals = u64::from_le_bytes(self.approvals);
+        let rejections = u64::from_le_bytes(self.rejections);
+        let num_signers = u64::from_le_bytes(self.num_signers);
+
+        // Every owner votes at most once.
+        let votes = approvals
+            .checked_add(rejections)
+            .ok_or(CustomError::IncrementError)?;
+        if votes > num_signers {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        self.status = if approvals >= threshold {
+            TransactionStatus::Approved
+        } else if num_signers - rejections < threshold {
+            TransactionStatus::Rejected
+        } else {
+            TransactionStatus::Active
+        } as u8;
+        Ok(())
+    }
+
+    /// Offsets of the signers, the instruction data and the end of the body.
+    fn body_offsets(&self) -> Result<(usize, usize, usize), ProgramError> {
+        let accounts_size = (u64::from_le_bytes(self.num_accounts) as usize)
+            .checked_mul(core::mem::size_of::<TransactionAccount>())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        let signers_size = (u64::from_le_bytes(self.num_signers) as usize)
+            .checked_mul(core::mem::size_of::<TransactionSigner>())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        let data_offset = accounts_size
+            .checked_add(signers_size)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        let end = data_offset
+            .checked_add(u64::from_le_bytes(self.data_len) as usize)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        Ok((accounts_size, data_offset, end))
+    }
+}
+
 impl<'a> Transaction<'a> {
-    pub fn read(
-        account: &AccountInfo,
-    ) -> Result<
-        (
-            &TransactionHeader,
-            &[TransactionAccount],
-            &[TransactionSigner],
-            &[u8],
-        ),
-        ProgramError,
-    > {
-        let data = unsafe { account.borrow_data_unchecked() };
-        Transaction::parse(data)
+    /// Reads a transaction account. The account is authenticated by program
+    /// ownership and its discriminator, since only `CreateTransaction` writes
+    /// one; callers check that it belongs to their multisig.
+    pub fn read(account: &AccountInfo) -> Result<TransactionParts<'_>, ProgramError> {
+        let (header, body) = TransactionHeader::load_account(account)?;
+        Transaction::split(header, body)
     }
 
+    /// Initializes a newly created transaction account.
     pub fn write(
         account: &AccountInfo,
         header: &TransactionHeader,
         accounts: &[TransactionAccount],
         signers: &[TransactionSigner],
         tx_data: &[u8],
+    ) -> Result<(), ProgramError> {
+        let body = TransactionHeader::init(account, header)?;
+        Transaction::write_body(header, body, accounts, signers, tx_data)
+    }
+
+    /// Replaces the content of a transaction account, which must already be
+    /// sized for `header`.
+    pub fn rewrite(
+        account: &AccountInfo,
+        header: &TransactionHeader,
+        accounts: &[TransactionAccount],
+        signers: &[TransactionSigner],
+        tx_data: &[u8],
     ) -> Result<(), ProgramError> {
         let data = unsafe { account.borrow_mut_data_unchecked() };
         let header_bytes = bytemuck::bytes_of(header);
-        let accounts_bytes = bytemuck::cast_slice::<TransactionAccount, u8>(accounts);
-        let signers_bytes = bytemuck::cast_slice::<TransactionSigner, u8>(signers);
+        data.get_mut(..header_bytes.len())
+            .ok_or(ProgramError::AccountDataTooSmall)?
+            .copy_from_slice(header_bytes);
+        let (_, body) = TransactionHeader::load_mut(data)?;
+        Transaction::write_body(header, body, accounts, signers, tx_data)
+    }
 
-        let accounts_offset = header_bytes.len();
-        let signers_offset = accounts_offset + accounts_bytes.len();
-        let data_offset = signers_offset + signers_bytes.len();
-        let tx_data_len = tx_data.len();
+    fn write_body(
+        header: &TransactionHeader,
+        body: &mut [u8],
+        accounts: &[TransactionAccount],
+        signers: &[TransactionSigner],
+        tx_data: &[u8],
+    ) -> Result<(), ProgramError> {
+        let (signers_offset, data_offset, _) = header.body_offsets()?;
 
-        data[..accounts_offset].copy_from_slice(header_bytes);
-        data[accounts_offset..signers_offset].copy_from_slice(accounts_bytes);
-        data[signers_offset..data_offset].copy_from_slice(signers_bytes);
-        data[data_offset..data_offset + tx_data_len].copy_from_slice(tx_data);
+        body[..signers_offset].copy_from_slice(bytemuck::cast_slice(accounts));
+        body[signers_offset..data_offset].copy_from_slice(bytemuck::cast_slice(signers));
+        body[data_offset..].copy_from_slice(tx_data);
 
         Ok(())
     }
 
-    /// Returns a mutable reference to transaction signers.
-    pub fn signers(account: &AccountInfo) -> Result<&mut [TransactionSigner], ProgramError> {
-        let data = unsafe { account.borrow_mut_data_unchecked() };
-        let header_size = core::mem::size_of::<TransactionHeader>();
-        let num_accounts: u64;
-        let num_signers: u64;
-        {
-            let header = bytemuck::try_from_bytes::<TransactionHeader>(&data[..header_size])
-                .map_err(|_| ProgramError::InvalidAccountData)?;
-            num_accounts = u64::from_le_bytes(header.num_accounts);
-            num_signers = u64::from_le_bytes(header.num_signers);
-        }
+    /// Loads the header and signers of a transaction account, mutably, to
+    /// record votes. Both come from a single borrow of the account data.
+    #[allow(clippy::mut_from_ref)]
+    pub fn load_signers_mut(
+        account: &AccountInfo,
+    ) -> Result<(&mut TransactionHeader, &mut [TransactionSigner]), ProgramError> {
+        let (header, body) = TransactionHeader::load_account_mut(account)?;
+        let (signers_offset, data_offset, _) = header.body_offsets()?;
+        let signers = bytemuck::cast_slice_mut::<u8, TransactionSigner>(
+            &mut body[signers_offset..data_offset],
+        );
+        Ok((header, signers))
+    }
 
-        let accounts_size = num_accounts as usize * core::mem::size_of::<TransactionAccount>();
-        let signers_size = num_signers as usize * core::mem::size_of::<TransactionSigner>();
-        let signers_offset = header_size + accounts_size;
-        let signers_data = &mut data[signers_offset..signers_offset + signers_size];
-        let signers = bytemuck::cast_slice_mut::<u8, TransactionSigner>(signers_data);
+    pub fn parse(data: &[u8]) -> Result<TransactionParts<'_>, ProgramError> {
+        let (header, body) = TransactionHeader::load(data)?;
+        Transaction::split(header, body)
+    }
 
-        Ok(signers)
+    fn split<'b>(
+        header: &'b TransactionHeader,
+        body: &'b [u8],
+    ) -> Result<TransactionParts<'b>, ProgramError> {
+        let (signers_offset, data_offset, _) = header.body_offsets()?;
+        let accounts = bytemuck::cast_slice::<u8, TransactionAccount>(&body[..signers_offset]);
+        let signers =
+            bytemuck::cast_slice::<u8, TransactionSigner>(&body[signers_offset..data_offset]);
+        Ok((header, accounts, signers, &body[data_offset..]))
     }
 
-    pub fn parse(
+    /// Canonical hash of what owners approve: the program id, signing vaults,
+    /// assertions, account metas and data of the transaction.
+    pub fn proposal_hash(
+        program_id: &Pubkey,
+        vaults: &TransactionVaults,
+        assertions: &TransactionAssertions,
+        accounts: &[TransactionAccount],
         data: &[u8],
-    ) -> Result<
-        (
-            &TransactionHeader,
-            &[TransactionAccount],
-            &[TransactionSigner],
-            &[u8],
-        ),
-        ProgramError,
-    > {
-        let header_size = core::mem::size_of::<TransactionHeader>();
-        let header = bytemuck::try_from_bytes::<TransactionHeader>(&data[..header_size])
-            .map_err(|_| ProgramError::InvalidAccountData)?;
-
-        let num_accounts = u64::from_le_bytes(header.num_accounts);
-        let accounts_size = num_accounts as usize * core::mem::size_of::<TransactionAccount>();
-        let accounts_data = &data[header_size..header_size + accounts_size];
-        let accounts = bytemuck::cast_slice::<u8, TransactionAccount>(accounts_data);
-
-        let num_signers = u64::from_le_bytes(header.num_signers);
-        let signers_size = num_signers as usize * core::mem::size_of::<TransactionSigner>();
-        let signers_offset = header_size + accounts_size;
-        let signers_data = &data[signers_offset..signers_offset + signers_size];
-        let signers = bytemuck::cast_slice::<u8, TransactionSigner>(signers_data);
-
-        let data_len = u64::from_le_bytes(header.data_len);
-        let data_offset = header_size + accounts_size + signers_size;
-        let tx_data = &data[data_offset..data_offset + data_len as usize];
-
-        Ok((header, accounts, signers, tx_data))
-    }
-
-    pub fn size(num_accounts: u64, num_signers: u64, data_len: u64) -> usize {
-        let header_size = core::mem::size_of::<TransactionHeader>();
-        let accounts_size = num_accounts as usize * core::mem::size_of::<TransactionAccount>();
-        let signers_size = num_signers as usize * core::mem::size_of::<TransactionSigner>();
-
-        header_size + accounts_size + signers_size + data_len as usize
+    ) -> [u8; 32] {
+        hash(&[
+            program_id.as_ref(),
+            bytemuck::bytes_of(vaults),
+            bytemuck::bytes_of(assertions),
+            bytemuck::cast_slice::<TransactionAccount, u8>(accounts),
+            data,
+        ])
+    }
+
+    /// Message an owner signs off-chain to approve the transaction stored at
+    /// `transaction`.
+    pub fn approval_message(transaction: &Pubkey, proposal_hash: &[u8; 32]) -> [u8; 32] {
+        hash(&[
+            APPROVAL_DOMAIN.as_bytes(),
+            crate::ID.as_ref(),
+            transaction.as_ref(),
+            proposal_hash,
+        ])
     }
 }
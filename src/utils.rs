@@ -0,0 +1,303 @@
+use core::mem::MaybeUninit;
+
+use pinocchio::{
+    account_info::AccountInfo,
+    cpi::slice_invoke_signed,
+    instruction::{AccountMeta, Instruction, Signer},
+    pubkey::Pubkey,
+    sysvars::{rent::Rent, Sysvar},
+    ProgramResult,
+};
+use pinocchio_system::instructions::{Allocate, Assign, CreateAccount, Transfer};
+
+use crate::{
+    constants::MAX_TRANSACTION_ACCOUNTS,
+    error::CustomError,
+    instruction::MultisigInstruction,
+    state::{AccountState, TransactionAccount, TransactionHeader},
+};
+
+/// Creates a program-owned PDA account of `space` bytes, funded by `payer`.
+///
+/// `CreateAccount` fails if the address already holds lamports, which anyone
+/// can cause by transferring to the predictable PDA. In that case the account
+/// is topped up to rent-exemption and then allocated and assigned instead.
+pub fn create_pda_account(
+    payer: &AccountInfo,
+    account: &AccountInfo,
+    space: usize,
+    signer: Signer,
+) -> ProgramResult {
+    let signers = [signer];
+    let lamports = Rent::get()?.minimum_balance(space);
+    let current_lamports = account.lamports();
+
+    if current_lamports == 0 {
+        return CreateAccount {
+            from: payer,
+            to: account,
+            space: space as u64,
+            lamports,
+            owner: &crate::ID,
+        }
+        .invoke_signed(&signers);
+    }
+
+    if current_lamports < lamports {
+        Transfer {
+            from: payer,
+            to: account,
+            lamports: lamports - current_lamports,
+        }
+        .invoke()?;
+    }
+
+    Allocate {
+        account,
+        space: space as u64,
+    }
+    .invoke_signed(&signers)?;
+
+    Assign {
+        account,
+        owner: &crate::ID,
+    }
+    .invoke_signed(&signers)
+}
+
+/// Resizes a program-owned account to `space` bytes, keeping it rent-exempt:
+/// `payer` funds any increase and is refunded any excess.
+pub fn resize_account(payer: &AccountInfo, account: &AccountInfo, space: usize) -> ProgramResult {
+    let lamports = Rent::get()?.minimum_balance(space);
+    let current_lamports = account.lamports();
+
+    if current_lamports < lamports {
+        Transfer {
+            from: payer,
+            to: account,
+            lamports: lamports - current_lamports,
+        }
+        .invoke()?;
+    } else if current_lamports > lamports {
+        let excess = current_lamports - lamports;
+        unsafe {
+            *account.borrow_mut_lamports_unchecked() -= excess;
+            *payer.borrow_mut_lamports_unchecked() += excess;
+        }
+    }
+
+    account.realloc(space, false)
+}
+
+/// Computes the SHA-256 hash of the concatenation of `vals`.
+pub fn hash(vals: &[&[u8]]) -> [u8; 32] {
+    #[cfg(target_os = "solana")]
+    {
+        let mut result = [0u8; 32];
+        unsafe {
+            pinocchio::syscalls::sol_sha256(
+                vals as *const _ as *const u8,
+                vals.len() as u64,
+                result.as_mut_ptr(),
+            );
+        }
+        result
+    }
+
+    #[cfg(not(target_os = "solana"))]
+    {
+        use sha2::{Digest, Sha256};
+
+        let mut hasher = Sha256::new();
+        for val in vals {
+            hasher.update(val);
+        }
+        hasher.finalize().into()
+    }
+}
+
+/// Finds the program derived address of `seeds` under `program_id` and its
+/// bump seed.
+///
+/// Unlike `pinocchio::pubkey::find_program_address`, which panics off-chain,
+/// this also derives the address off-chain, for clients.
+pub fn find_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> Option<(Pubkey, u8)> {
+    #[cfg(target_os = "solana")]
+    {
+        pinocchio::pubkey::try_find_program_address(seeds, program_id)
+    }
+
+    #[cfg(not(target_os = "solana"))]
+    {
+        use curve25519_dalek::edwards::CompressedEdwardsY;
+        use pinocchio::pubkey::{MAX_SEEDS, MAX_SEED_LEN};
+
+        if seeds.len() >= MAX_SEEDS || seeds.iter().any(|seed| seed.len() > MAX_SEED_LEN) {
+            return None;
+        }
+        (0..=u8::MAX).rev().find_map(|bump| {
+            let bump = [bump];
+            let mut vals: [&[u8]; MAX_SEEDS + 2] = [&[]; MAX_SEEDS + 2];
+            vals[..seeds.len()].copy_from_slice(seeds);
+            vals[seeds.len()] = &bump;
+            vals[seeds.len() + 1] = program_id;
+            vals[seeds.len() + 2] = b"ProgramDerivedAddress";
+            let address = hash(&vals[..seeds.len() + 3]);
+            // Addresses on the ed25519 curve could have a private key.
+            CompressedEdwardsY(address)
+                .decompress()
+                .is_none()
+                .then_some((address, bump[0]))
+        })
+    }
+}
+
+/// Checks that a transaction of `multisig` invoking `program_id` with
+/// `accounts` and `data` does not expose the state of the multisig.
+///
+/// Transactions may only invoke the instructions of this program that are
+/// [`MultisigInstruction::is_invocable`], and may list the multisig as
+/// writable only to change its configuration. When they execute a
+/// transaction of another multisig, only the executor may sign, and only as
+/// the executor: any other signer would lend the vault to a transaction this
+/// multisig never approved, outside of its program filter.
+pub fn check_invocation(
+    multisig: &Pubkey,
+    program_id: &Pubkey,
+    accounts: &[TransactionAccount],
+    data: &[u8],
+) -> ProgramResult {
+    let mut governance = false;
+    if program_id == &crate::ID {
+        let instruction = data
+            .first()
+            .and_then(|discriminator| MultisigInstruction::try_from(discriminator).ok())
+            .ok_or(CustomError::ProgramNotAllowed)?;
+        if !instruction.is_invocable() {
+            return Err(CustomError::ProgramNotAllowed.into());
+        }
+        governance = instruction.is_governance();
+
+        if matches!(
+            instruction,
+            MultisigInstruction::ExecuteTransaction | MultisigInstruction::ApproveAndExecute
+        ) {
+            // Accounts listed twice share their privileges, so the executor
+            // cannot reappear either.
+            if let Some((executor, rest)) = accounts.split_first() {
+                if rest
+                    .iter()
+                    .any(|account| account.is_signer != 0 || account.pubkey == executor.pubkey)
+                {
+                    return Err(CustomError::ProtectedAccount.into());
+                }
+            }
+        }
+    }
+
+    if !governance
+        && accounts
+            .iter()
+            .any(|account| account.is_writable != 0 && account.pubkey == *multisig)
+    {
+        return Err(CustomError::ProtectedAccount.into());
+    }
+    Ok(())
+}
+
+/// Checks that none of the writable `accounts`, passed as `account_infos`,
+/// is a transaction of `multisig`.
+pub fn check_transaction_accounts(
+    multisig: &Pubkey,
+    accounts: &[TransactionAccount],
+    account_infos: &[AccountInfo],
+) -> ProgramResult {
+    for (account, info) in accounts.iter().zip(account_infos) {
+        if account.is_writable != 0 && is_transaction_of(multisig, info) {
+            return Err(CustomError::ProtectedAccount.into());
+        }
+    }
+    Ok(())
+}
+
+/// Checks that `account_infos` are the writable `accounts`, in order, and that
+/// none of them is a transaction of `multisig`.
+pub fn check_writable_accounts(
+    multisig: &Pubkey,
+    accounts: &[TransactionAccount],
+    account_infos: &[AccountInfo],
+) -> ProgramResult {
+    let writable = accounts.iter().filter(|account| account.is_writable != 0);
+    for (account, info) in writable.zip(account_infos) {
+        if info.key() != &account.pubkey {
+            return Err(CustomError::AccountMismatch.into());
+        }
+        if is_transaction_of(multisig, info) {
+            return Err(CustomError::ProtectedAccount.into());
+        }
+    }
+    Ok(())
+}
+
+fn is_transaction_of(multisig: &Pubkey, info: &AccountInfo) -> bool {
+    info.is_owned_by(&crate::ID)
+        && TransactionHeader::load(unsafe { info.borrow_data_unchecked() })
+            .is_ok_and(|(header, _)| header.multisig == *multisig)
+}
+
+/// Invokes `program` with the approved `accounts` and `data`, signing with
+/// `signers`.
+///
+/// `account_infos` must list exactly the approved accounts, in order. The
+/// account metas passed to the invoked program are built from the approved
+/// flags only, so the caller cannot escalate an account to writable or signer.
+pub fn invoke_transaction(
+    program: &AccountInfo,
+    accounts: &[TransactionAccount],
+    data: &[u8],
+    account_infos: &[AccountInfo],
+    signers: &[Signer],
+) -> ProgramResult {
+    if accounts.len() > MAX_TRANSACTION_ACCOUNTS || account_infos.len() != accounts.len() {
+        return Err(CustomError::AccountMismatch.into());
+    }
+
+    const UNINIT_META: MaybeUninit<AccountMeta> = MaybeUninit::uninit();
+    let mut metas = [UNINIT_META; MAX_TRANSACTION_ACCOUNTS];
+    let mut infos: [&AccountInfo; MAX_TRANSACTION_ACCOUNTS] = [program; MAX_TRANSACTION_ACCOUNTS];
+
+    for (index, (account, info)) in accounts.iter().zip(account_infos).enumerate() {
+        if info.key() != &account.pubkey {
+            return Err(CustomError::AccountMismatch.into());
+        }
+        // Signer privileges are checked by the runtime: the account must
+        // either have signed the outer transaction or be a signing vault.
+        // This is also how a vault approves as an owner of another multisig
+        // when the transaction invokes this program.
+        if account.is_writable != 0 && !info.is_writable() {
+            return Err(CustomError::AccountMismatch.into());
+        }
+
+        metas[index].write(AccountMeta::new(
+            &account.pubkey,
+            account.is_writable != 0,
+            account.is_signer != 0,
+        ));
+        infos[index] = info;
+    }
+
+    // SAFETY: The first `accounts.len()` metas have been initialized.
+    let metas = unsafe {
+        core::slice::from_raw_parts(metas.as_ptr() as *const AccountMeta, accounts.len())
+    };
+
+    slice_invoke_signed(
+        &Instruction {
+            program_id: program.key(),
+            data,
+            accounts: metas,
+        },
+        &infos[..accounts.len()],
+        signers,
+    )
+}
@@ -1,16 +1,36 @@
+use bytemuck::Zeroable;
 use mollusk_svm::result::Check;
 use mollusk_svm::Mollusk;
-use p_multisig::constants::{MULTISIG_SEED, TRANSACTION_SEED};
-use p_multisig::state::MultisigHeader;
+use p_multisig::constants::{
+    MAX_EXECUTORS, MAX_FILTERED_PROGRAMS, MAX_SIGNING_VAULTS, MAX_THRESHOLD_POLICIES,
+    MULTISIG_SEED, TRANSACTION_SEED,
+};
+use p_multisig::error::CustomError;
+use p_multisig::instruction::InlineTransactionHeader;
+use p_multisig::state::{
+    AccountState, ExecutePolicy, Multisig, MultisigHeader, ProgramFilter, ThresholdPolicy,
+    Transaction,
+};
+use solana_instructions_sysvar::{construct_instructions_data, store_current_index_checked};
 use solana_sdk::account::Account;
 use solana_sdk::instruction::{AccountMeta, Instruction};
 use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::program_error::ProgramError;
 use solana_sdk::pubkey;
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::sysvar::{
+    self,
+    instructions::{self as instructions_sysvar, BorrowedAccountMeta, BorrowedInstruction},
+};
 extern crate alloc;
 use alloc::vec;
+use alloc::vec::Vec;
 
-use p_multisig::state::{TransactionAccount, TransactionHeader, TransactionSigner};
+use p_multisig::state::{
+    AssertionKind, TransactionAccount, TransactionAssertion, TransactionAssertions,
+    TransactionHeader, TransactionSigner, TransactionStatus, TransactionVaults,
+};
 use p_multisig::ID;
 
 pub const PROGRAM: Pubkey = Pubkey::new_from_array(ID);
@@ -20,8 +40,145 @@ pub const PAYER: Pubkey = pubkey!("JAbjHPzyDkvkCRxTL8usK4g1sQUYTX3f42MvfCqV9LVS"
 pub const INITIAL_COUNT: u64 = 42;
 
 pub fn mollusk() -> Mollusk {
-    let mollusk = Mollusk::new(&PROGRAM, "target/deploy/p_multisig");
-    mollusk
+    Mollusk::new(&PROGRAM, "target/deploy/p_multisig")
+}
+
+/// Builds an initialized multisig account owned by the program.
+pub fn multisig_account(header: &MultisigHeader, owners: &[Pubkey]) -> Account {
+    let mut data = Vec::new();
+    data.extend_from_slice(bytemuck::bytes_of(header));
+    data.extend_from_slice(bytemuck::cast_slice::<Pubkey, u8>(owners));
+
+    let mut account = Account::new(LAMPORTS_PER_SOL, data.len(), &PROGRAM);
+    account.data = data;
+    account
+}
+
+/// Derives vault `index` of a multisig.
+pub fn vault(multisig: &Pubkey, index: u8) -> (Pubkey, u8) {
+    let (vault, bump) = Multisig::vault_address(multisig.as_array(), index).unwrap();
+    (Pubkey::new_from_array(vault), bump)
+}
+
+/// Builds the signing vaults entry for the given vault indices of a multisig.
+pub fn signing_vaults(multisig: &Pubkey, indices: &[u8]) -> TransactionVaults {
+    let mut vaults = TransactionVaults {
+        num_vaults: indices.len() as u8,
+        indices: [0; MAX_SIGNING_VAULTS],
+        bumps: [0; MAX_SIGNING_VAULTS],
+    };
+    for (i, index) in indices.iter().enumerate() {
+        vaults.indices[i] = *index;
+        vaults.bumps[i] = vault(multisig, *index).1;
+    }
+    vaults
+}
+
+/// Derives a transaction PDA and returns a header for it, proposed by owner 1.
+pub fn transaction_header(
+    index: u64,
+    multisig: &Pubkey,
+    program_id: &Pubkey,
+    num_accounts: u64,
+    num_signers: u64,
+    data_len: u64,
+) -> (Pubkey, TransactionHeader) {
+    let index_bytes = u64::to_le_bytes(index);
+    let (transaction, transaction_bump) = Pubkey::find_program_address(
+        &[TRANSACTION_SEED.as_bytes(), multisig.as_ref(), &index_bytes],
+        &PROGRAM,
+    );
+
+    let header = TransactionHeader {
+        discriminator: TransactionHeader::DISCRIMINATOR,
+        multisig: multisig.to_bytes(),
+        proposer: [0x01; 32],
+        rent_payer: [0x01; 32],
+        program_id: program_id.to_bytes(),
+        num_accounts: u64::to_le_bytes(num_accounts),
+        num_signers: u64::to_le_bytes(num_signers),
+        data_len: u64::to_le_bytes(data_len),
+        status: TransactionStatus::Active as u8,
+        approvals: [0; 8],
+        rejections: [0; 8],
+        index: index_bytes,
+        bump: transaction_bump,
+        proposal_hash: [0; 32],
+        vaults: signing_vaults(multisig, &[0]),
+        assertions: TransactionAssertions::zeroed(),
+    };
+
+    (transaction, header)
+}
+
+/// Builds an initialized transaction account owned by the program, with its
+/// proposal hash and votes computed from its content and, if it is open to
+/// votes, its status derived from `threshold`.
+pub fn transaction_account(
+    header: &TransactionHeader,
+    accounts: &[TransactionAccount],
+    signers: &[TransactionSigner],
+    data: &[u8],
+    threshold: u64,
+) -> Account {
+    let mut header = *header;
+    header.proposal_hash = Transaction::proposal_hash(
+        &header.program_id,
+        &header.vaults,
+        &header.assertions,
+        accounts,
+        data,
+    );
+    let approvals = signers.iter().filter(|s| s.signed == 255).count() as u64;
+    let rejections = signers.iter().filter(|s| s.rejected == 255).count() as u64;
+    header.approvals = approvals.to_le_bytes();
+    header.rejections = rejections.to_le_bytes();
+    if header.is_open().unwrap() {
+        header.update_status(threshold).unwrap();
+    }
+
+    let mut account_data = Vec::new();
+    account_data.extend_from_slice(bytemuck::bytes_of(&header));
+    account_data.extend_from_slice(bytemuck::cast_slice::<TransactionAccount, u8>(accounts));
+    account_data.extend_from_slice(bytemuck::cast_slice::<TransactionSigner, u8>(signers));
+    account_data.extend_from_slice(data);
+
+    let mut account = Account::new(LAMPORTS_PER_SOL, account_data.len(), &PROGRAM);
+    account.data = account_data;
+    account
+}
+
+/// Derives a multisig PDA and returns a header for it with the given owners
+/// count and threshold.
+pub fn multisig_header(seed: u64, num_owners: u64, threshold: u64) -> (Pubkey, MultisigHeader) {
+    let seed_bytes = u64::to_le_bytes(seed);
+    let (multisig, multisig_bump) =
+        Pubkey::find_program_address(&[(MULTISIG_SEED.as_bytes()), &seed_bytes], &PROGRAM);
+
+    let header = MultisigHeader {
+        discriminator: MultisigHeader::DISCRIMINATOR,
+        seed: seed_bytes,
+        num_owners: u64::to_le_bytes(num_owners),
+        threshold: u64::to_le_bytes(threshold),
+        nonce: 0,
+        bump: multisig_bump,
+        guardian: [0; 32],
+        pause_threshold: u64::to_le_bytes(1),
+        paused: 0,
+        execute_policy: ExecutePolicy::AnyMember as u8,
+        num_executors: 0,
+        executors: [[0; 32]; MAX_EXECUTORS],
+        transaction_index: [0; 8],
+        stale_transaction_index: [0; 8],
+        sorted_owners: 0,
+        num_threshold_policies: 0,
+        threshold_policies: [ThresholdPolicy::zeroed(); MAX_THRESHOLD_POLICIES],
+        program_filter: ProgramFilter::None as u8,
+        num_filtered_programs: 0,
+        filtered_programs: [[0; 32]; MAX_FILTERED_PROGRAMS],
+    };
+
+    (multisig, header)
 }
 
 #[test]
@@ -32,7 +189,7 @@ fn test_create_multisig() {
     let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
 
     let owner_1 = Pubkey::new_from_array([0x01; 32]);
-    let owner_1_account = Account::new(1 * LAMPORTS_PER_SOL, 0, &system_program);
+    let owner_1_account = Account::new(LAMPORTS_PER_SOL, 0, &system_program);
 
     let owner_2 = Pubkey::new_from_array([0x02; 32]);
     let owner_3 = Pubkey::new_from_array([0x03; 32]);
@@ -46,11 +203,26 @@ fn test_create_multisig() {
 
     // Create the instruction data.
     let multisig_header = MultisigHeader {
+        discriminator: MultisigHeader::DISCRIMINATOR,
         seed: seed_bytes,
         num_owners: u64::to_le_bytes(3),
         threshold: u64::to_le_bytes(3),
         nonce: 0,
         bump: multisig_bump,
+        guardian: [0; 32],
+        pause_threshold: u64::to_le_bytes(1),
+        paused: 0,
+        execute_policy: ExecutePolicy::AnyMember as u8,
+        num_executors: 0,
+        executors: [[0; 32]; MAX_EXECUTORS],
+        transaction_index: [0; 8],
+        stale_transaction_index: [0; 8],
+        sorted_owners: 0,
+        num_threshold_policies: 0,
+        threshold_policies: [ThresholdPolicy::zeroed(); MAX_THRESHOLD_POLICIES],
+        program_filter: ProgramFilter::None as u8,
+        num_filtered_programs: 0,
+        filtered_programs: [[0; 32]; MAX_FILTERED_PROGRAMS],
     };
 
     // instruction discriminator = 0
@@ -76,7 +248,7 @@ fn test_create_multisig() {
 
     mollusk.process_and_validate_instruction(
         &instruction,
-        &vec![
+        &[
             (owner_1, owner_1_account),
             (multisig, multisig_account),
             (system_program, system_account),
@@ -85,6 +257,151 @@ fn test_create_multisig() {
     );
 }
 
+#[test]
+fn test_create_multisig_duplicate_owners() {
+    let mollusk = mollusk();
+
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+
+    let create = |owners: &[Pubkey], sorted_owners: u8, checks: &[Check]| {
+        let (multisig, mut multisig_header) = multisig_header(1, owners.len() as u64, 2);
+        multisig_header.sorted_owners = sorted_owners;
+
+        // instruction discriminator = 0
+        let mut ser_instruction_data = vec![0];
+        ser_instruction_data.extend_from_slice(bytemuck::bytes_of(&multisig_header));
+        ser_instruction_data.extend_from_slice(bytemuck::cast_slice::<Pubkey, u8>(owners));
+
+        let instruction = Instruction::new_with_bytes(
+            PROGRAM,
+            &ser_instruction_data,
+            vec![
+                AccountMeta::new(owner_1, true),
+                AccountMeta::new(multisig, true),
+                AccountMeta::new_readonly(system_program, false),
+            ],
+        );
+
+        mollusk.process_and_validate_instruction(
+            &instruction,
+            &[
+                (owner_1, Account::new(LAMPORTS_PER_SOL, 0, &system_program)),
+                (multisig, Account::new(0, 0, &system_program)),
+                (system_program, system_account.clone()),
+            ],
+            checks,
+        );
+    };
+
+    let invalid = [Check::err(ProgramError::InvalidInstructionData)];
+    // One key cannot hold two votes.
+    create(&[owner_1, owner_2, owner_1], 0, &invalid);
+    // Owners declared sorted must be strictly ascending.
+    create(&[owner_2, owner_1], 255, &invalid);
+    create(&[owner_1, owner_1], 255, &invalid);
+    create(&[owner_1, owner_2], 255, &[Check::success()]);
+}
+
+#[test]
+fn test_create_multisig_non_canonical_bump() {
+    let mollusk = mollusk();
+
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+
+    // Another valid bump for the same seed yields a different address.
+    let (_, mut multisig_header) = multisig_header(1, 2, 2);
+    let (multisig, bump) = (0..multisig_header.bump)
+        .rev()
+        .find_map(|bump| {
+            Pubkey::create_program_address(
+                &[MULTISIG_SEED.as_bytes(), &multisig_header.seed, &[bump]],
+                &PROGRAM,
+            )
+            .ok()
+            .map(|address| (address, bump))
+        })
+        .unwrap();
+    multisig_header.bump = bump;
+
+    // instruction discriminator = 0
+    let mut ser_instruction_data = vec![0];
+    ser_instruction_data.extend_from_slice(bytemuck::bytes_of(&multisig_header));
+    ser_instruction_data.extend_from_slice(bytemuck::cast_slice::<Pubkey, u8>(&[owner_1, owner_2]));
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &ser_instruction_data,
+        vec![
+            AccountMeta::new(owner_1, true),
+            AccountMeta::new(multisig, true),
+            AccountMeta::new_readonly(system_program, false),
+        ],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (owner_1, Account::new(LAMPORTS_PER_SOL, 0, &system_program)),
+            (multisig, Account::new(0, 0, &system_program)),
+            (system_program, system_account),
+        ],
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_create_multisig_with_payer() {
+    let mollusk = mollusk();
+
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    // The owner holds no SOL, rent is paid by a separate fee payer.
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_1_account = Account::new(0, 0, &system_program);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+    let payer_account = Account::new(LAMPORTS_PER_SOL, 0, &system_program);
+
+    let (multisig, multisig_header) = multisig_header(1, 2, 2);
+    let multisig_account = Account::new(0, 0, &system_program);
+
+    // instruction discriminator = 0
+    let mut ser_instruction_data = vec![0];
+    ser_instruction_data.extend_from_slice(bytemuck::bytes_of(&multisig_header));
+    ser_instruction_data.extend_from_slice(bytemuck::cast_slice::<Pubkey, u8>(&[owner_1, owner_2]));
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &ser_instruction_data,
+        vec![
+            AccountMeta::new_readonly(owner_1, true),
+            AccountMeta::new(multisig, true),
+            AccountMeta::new_readonly(system_program, false),
+            AccountMeta::new(PAYER, true),
+        ],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (owner_1, owner_1_account),
+            (multisig, multisig_account),
+            (system_program, system_account),
+            (PAYER, payer_account),
+        ],
+        &[
+            Check::success(),
+            Check::account(&owner_1).lamports(0).build(),
+            Check::account(&multisig).owner(&PROGRAM).build(),
+        ],
+    );
+}
+
 #[test]
 fn test_create_transaction() {
     let mollusk = mollusk();
@@ -93,7 +410,7 @@ fn test_create_transaction() {
 
     // Owners
     let owner_1 = Pubkey::new_from_array([0x01; 32]);
-    let owner_1_account = Account::new(1 * LAMPORTS_PER_SOL, 0, &system_program);
+    let owner_1_account = Account::new(LAMPORTS_PER_SOL, 0, &system_program);
     let owner_2 = Pubkey::new_from_array([0x02; 32]);
     let owner_3 = Pubkey::new_from_array([0x03; 32]);
     let owners_vec = vec![owner_1, owner_2, owner_3];
@@ -104,14 +421,29 @@ fn test_create_transaction() {
         &[(MULTISIG_SEED.as_bytes()), &seed_bytes],
         &PROGRAM,
     );
-    let mut multisig_account = Account::new(1 * LAMPORTS_PER_SOL, 0, &system_program);
+    let mut multisig_account = Account::new(LAMPORTS_PER_SOL, 0, &PROGRAM);
 
     let multisig_header = MultisigHeader {
+        discriminator: MultisigHeader::DISCRIMINATOR,
         seed: seed_bytes,
         num_owners: u64::to_le_bytes(3),
         threshold: u64::to_le_bytes(2),
         nonce: 0,
         bump: multisig_bump,
+        guardian: [0; 32],
+        pause_threshold: u64::to_le_bytes(1),
+        paused: 0,
+        execute_policy: ExecutePolicy::AnyMember as u8,
+        num_executors: 0,
+        executors: [[0; 32]; MAX_EXECUTORS],
+        transaction_index: [0; 8],
+        stale_transaction_index: [0; 8],
+        sorted_owners: 0,
+        num_threshold_policies: 0,
+        threshold_policies: [ThresholdPolicy::zeroed(); MAX_THRESHOLD_POLICIES],
+        program_filter: ProgramFilter::None as u8,
+        num_filtered_programs: 0,
+        filtered_programs: [[0; 32]; MAX_FILTERED_PROGRAMS],
     };
 
     let header_bytes = bytemuck::bytes_of(&multisig_header);
@@ -123,22 +455,34 @@ fn test_create_transaction() {
         .copy_from_slice(owners_bytes);
 
     // Transaction
-    let tx_seed_bytes = u64::to_le_bytes(2);
+    let tx_index_bytes = u64::to_le_bytes(1);
     let (transaction, transaction_bump) = solana_sdk::pubkey::Pubkey::find_program_address(
-        &[(TRANSACTION_SEED.as_bytes()), &tx_seed_bytes],
+        &[
+            TRANSACTION_SEED.as_bytes(),
+            multisig.as_ref(),
+            &tx_index_bytes,
+        ],
         &PROGRAM,
     );
     let transaction_account = Account::new(0, 0, &system_program);
 
     let transaction_header = TransactionHeader {
+        discriminator: TransactionHeader::DISCRIMINATOR,
         multisig: multisig.to_bytes(),
+        proposer: [0; 32],
+        rent_payer: [0; 32],
         program_id: system_program.to_bytes(),
         num_accounts: u64::to_le_bytes(1),
         num_signers: u64::to_le_bytes(3),
         data_len: u64::to_le_bytes(4),
-        executed: 0,
-        seed: tx_seed_bytes,
+        status: TransactionStatus::Active as u8,
+        approvals: [0; 8],
+        rejections: [0; 8],
+        index: tx_index_bytes,
         bump: transaction_bump,
+        proposal_hash: [0; 32],
+        vaults: signing_vaults(&multisig, &[0]),
+        assertions: TransactionAssertions::zeroed(),
     };
 
     // Transaction accounts (just one for this test)
@@ -153,14 +497,17 @@ fn test_create_transaction() {
         TransactionSigner {
             pubkey: owner_1.to_bytes(),
             signed: 255,
+            rejected: 0,
         },
         TransactionSigner {
             pubkey: owner_2.to_bytes(),
             signed: 0,
+            rejected: 0,
         },
         TransactionSigner {
             pubkey: owner_3.to_bytes(),
             signed: 0,
+            rejected: 0,
         },
     ];
 
@@ -176,6 +523,16 @@ fn test_create_transaction() {
         .extend_from_slice(bytemuck::cast_slice::<TransactionSigner, u8>(&tx_signers));
     ser_instruction_data.extend_from_slice(&tx_data);
 
+    // The program stores the proposal hash computed from the content.
+    let proposal_hash = Transaction::proposal_hash(
+        &system_program.to_bytes(),
+        &transaction_header.vaults,
+        &transaction_header.assertions,
+        &tx_accounts,
+        &tx_data,
+    );
+    let proposal_hash_offset = core::mem::offset_of!(TransactionHeader, proposal_hash);
+
     let instruction = Instruction::new_with_bytes(
         PROGRAM,
         &ser_instruction_data,
@@ -184,17 +541,2899 @@ fn test_create_transaction() {
             AccountMeta::new(transaction, true),
             AccountMeta::new(multisig, true),
             AccountMeta::new_readonly(system_program, false),
+            // The writable accounts of the transaction.
+            AccountMeta::new(owner_1, false),
         ],
     );
 
     mollusk.process_and_validate_instruction(
         &instruction,
-        &vec![
+        &[
             (owner_1, owner_1_account),
             (transaction, transaction_account),
             (multisig, multisig_account),
             (system_program, system_account),
         ],
+        &[
+            Check::success(),
+            Check::account(&transaction)
+                .data_slice(proposal_hash_offset, &proposal_hash)
+                .build(),
+            // The creator's approval is counted.
+            Check::account(&transaction)
+                .data_slice(
+                    core::mem::offset_of!(TransactionHeader, approvals),
+                    &u64::to_le_bytes(1),
+                )
+                .build(),
+            Check::account(&multisig)
+                .data_slice(
+                    core::mem::offset_of!(MultisigHeader, transaction_index),
+                    &u64::to_le_bytes(1),
+                )
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_pause_multisig_by_guardian() {
+    let mollusk = mollusk();
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+    let guardian = Pubkey::new_from_array([0x09; 32]);
+
+    let (multisig, mut header) = multisig_header(1, 2, 2);
+    header.guardian = guardian.to_bytes();
+    header.pause_threshold = u64::to_le_bytes(2);
+    let multisig_account = multisig_account(&header, &[owner_1, owner_2]);
+
+    // instruction discriminator = 4
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &[4],
+        vec![
+            AccountMeta::new(multisig, false),
+            AccountMeta::new_readonly(guardian, true),
+        ],
+    );
+
+    let paused_offset = core::mem::offset_of!(MultisigHeader, paused);
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[(multisig, multisig_account), (guardian, Account::default())],
+        &[
+            Check::success(),
+            Check::account(&multisig)
+                .data_slice(paused_offset, &[255])
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_pause_multisig_without_quorum() {
+    let mollusk = mollusk();
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+    let owner_3 = Pubkey::new_from_array([0x03; 32]);
+
+    let (multisig, mut header) = multisig_header(1, 3, 3);
+    header.pause_threshold = u64::to_le_bytes(2);
+    let multisig_account = multisig_account(&header, &[owner_1, owner_2, owner_3]);
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &[4],
+        vec![
+            AccountMeta::new(multisig, false),
+            AccountMeta::new_readonly(owner_1, true),
+        ],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[(multisig, multisig_account), (owner_1, Account::default())],
+        &[Check::err(ProgramError::Custom(
+            CustomError::NotEnoughSigners as u32,
+        ))],
+    );
+}
+
+#[test]
+fn test_unpause_multisig_requires_threshold() {
+    let mollusk = mollusk();
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+
+    let (multisig, mut header) = multisig_header(1, 2, 2);
+    header.paused = 255;
+    let owners = [owner_1, owner_2];
+
+    // instruction discriminator = 5
+    // A single owner is enough to pause, but not to unpause.
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &[5],
+        vec![
+            AccountMeta::new(multisig, false),
+            AccountMeta::new_readonly(owner_1, true),
+        ],
+    );
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (multisig, multisig_account(&header, &owners)),
+            (owner_1, Account::default()),
+        ],
+        &[Check::err(ProgramError::Custom(
+            CustomError::NotEnoughSigners as u32,
+        ))],
+    );
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &[5],
+        vec![
+            AccountMeta::new(multisig, false),
+            AccountMeta::new_readonly(owner_1, true),
+            AccountMeta::new_readonly(owner_2, true),
+        ],
+    );
+    let paused_offset = core::mem::offset_of!(MultisigHeader, paused);
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (multisig, multisig_account(&header, &owners)),
+            (owner_1, Account::default()),
+            (owner_2, Account::default()),
+        ],
+        &[
+            Check::success(),
+            Check::account(&multisig)
+                .data_slice(paused_offset, &[0])
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_approve_transaction_when_paused() {
+    let mollusk = mollusk();
+
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+
+    let (multisig, mut header) = multisig_header(1, 2, 2);
+    header.paused = 255;
+    let multisig_account = multisig_account(&header, &[owner_1, owner_2]);
+
+    let tx_index_bytes = u64::to_le_bytes(1);
+    let (transaction, transaction_bump) = Pubkey::find_program_address(
+        &[
+            TRANSACTION_SEED.as_bytes(),
+            multisig.as_ref(),
+            &tx_index_bytes,
+        ],
+        &PROGRAM,
+    );
+    let transaction_header = TransactionHeader {
+        discriminator: TransactionHeader::DISCRIMINATOR,
+        multisig: multisig.to_bytes(),
+        proposer: [0x01; 32],
+        rent_payer: [0x01; 32],
+        program_id: system_program.to_bytes(),
+        num_accounts: u64::to_le_bytes(0),
+        num_signers: u64::to_le_bytes(2),
+        data_len: u64::to_le_bytes(0),
+        status: TransactionStatus::Active as u8,
+        approvals: [0; 8],
+        rejections: [0; 8],
+        index: tx_index_bytes,
+        bump: transaction_bump,
+        proposal_hash: [0; 32],
+        vaults: signing_vaults(&multisig, &[0]),
+        assertions: TransactionAssertions::zeroed(),
+    };
+    let tx_signers = [
+        TransactionSigner {
+            pubkey: owner_1.to_bytes(),
+            signed: 255,
+            rejected: 0,
+        },
+        TransactionSigner {
+            pubkey: owner_2.to_bytes(),
+            signed: 0,
+            rejected: 0,
+        },
+    ];
+    let mut transaction_data = Vec::new();
+    transaction_data.extend_from_slice(bytemuck::bytes_of(&transaction_header));
+    transaction_data.extend_from_slice(bytemuck::cast_slice::<TransactionSigner, u8>(&tx_signers));
+    let mut transaction_account = Account::new(LAMPORTS_PER_SOL, transaction_data.len(), &PROGRAM);
+    transaction_account.data = transaction_data;
+
+    // instruction discriminator = 2
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &[2],
+        vec![
+            AccountMeta::new(owner_2, true),
+            AccountMeta::new(transaction, false),
+            AccountMeta::new_readonly(multisig, false),
+            AccountMeta::new_readonly(system_program, false),
+        ],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (owner_2, Account::default()),
+            (transaction, transaction_account),
+            (multisig, multisig_account),
+            (system_program, system_account),
+        ],
+        &[Check::err(ProgramError::Custom(
+            CustomError::MultisigPaused as u32,
+        ))],
+    );
+}
+
+#[test]
+fn test_approve_stale_transaction() {
+    let mollusk = mollusk();
+
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+
+    // The configuration changed after transaction 2 was created.
+    let (multisig, mut header) = multisig_header(1, 2, 2);
+    header.transaction_index = u64::to_le_bytes(3);
+    header.stale_transaction_index = u64::to_le_bytes(3);
+
+    let (transaction, transaction_header) =
+        transaction_header(2, &multisig, &system_program, 0, 2, 0);
+    let tx_signers = [
+        TransactionSigner {
+            pubkey: owner_1.to_bytes(),
+            signed: 255,
+            rejected: 0,
+        },
+        TransactionSigner {
+            pubkey: owner_2.to_bytes(),
+            signed: 0,
+            rejected: 0,
+        },
+    ];
+
+    // instruction discriminator = 2
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &[2],
+        vec![
+            AccountMeta::new(owner_2, true),
+            AccountMeta::new(transaction, false),
+            AccountMeta::new_readonly(multisig, false),
+            AccountMeta::new_readonly(system_program, false),
+        ],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (owner_2, Account::default()),
+            (
+                transaction,
+                transaction_account(&transaction_header, &[], &tx_signers, &[], 2),
+            ),
+            (multisig, multisig_account(&header, &[owner_1, owner_2])),
+            (system_program, system_account),
+        ],
+        &[Check::err(ProgramError::Custom(
+            CustomError::StaleTransaction as u32,
+        ))],
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_decode_events() {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use p_multisig::event::{decode_events, Event, EventKind};
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let (multisig, _) = multisig_header(1, 1, 1);
+    let transaction = Pubkey::new_from_array([0x07; 32]);
+
+    let event = Event::new(
+        EventKind::Approve,
+        multisig.as_array(),
+        transaction.as_array(),
+        owner_1.as_array(),
+        2,
+    );
+    let data = STANDARD.encode(bytemuck::bytes_of(&event));
+    let other_program = Pubkey::new_from_array([0x08; 32]);
+
+    let logs = vec![
+        format!("Program {} invoke [1]", PROGRAM),
+        format!("Program {} invoke [2]", other_program),
+        // Data logged by another program must be ignored.
+        format!("Program data: {}", data),
+        format!("Program {} success", other_program),
+        format!("Program data: {}", data),
+        format!("Program {} consumed 1000 of 200000 compute units", PROGRAM),
+        format!("Program {} success", PROGRAM),
+    ];
+
+    let events = decode_events(&logs);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].kind(), Ok(EventKind::Approve));
+    assert_eq!(events[0].transaction, transaction.to_bytes());
+    assert_eq!(u64::from_le_bytes(events[0].approval_count), 2);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_inspect_transaction() {
+    use p_multisig::state::KnownInstruction;
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+
+    let (multisig, multisig_account, _, transaction_account) =
+        approved_transfer(&recipient, 1_000, 0, |_| {});
+    let (vault, _) = vault(&multisig, 0);
+    let (multisig_header, _) = Multisig::parse(&multisig_account.data).unwrap();
+
+    let summary = Transaction::inspect(&transaction_account.data, multisig_header).unwrap();
+    assert_eq!(summary.multisig, multisig.to_bytes());
+    assert_eq!(summary.accounts.len(), 2);
+    assert!(summary.accounts[0].is_signer);
+    assert_eq!(summary.approvals[0].owner, owner_1.to_bytes());
+    assert!(summary.approvals[0].approved);
+    assert_eq!(summary.approvals[1].owner, owner_2.to_bytes());
+    assert!(!summary.approvals[1].approved);
+    assert_eq!(summary.approval_count, 1);
+    assert!(!summary.threshold_met);
+    assert_eq!(
+        summary.instruction,
+        Some(KnownInstruction::SystemTransfer {
+            from: vault.to_bytes(),
+            to: recipient.to_bytes(),
+            lamports: 1_000,
+        })
+    );
+    assert!(summary.to_string().contains("awaiting approvals"));
+
+    // SPL Token transfer: tag 3 followed by the amount.
+    let token_program = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+    let mut data = vec![3];
+    data.extend_from_slice(&500u64.to_le_bytes());
+    assert_eq!(
+        KnownInstruction::decode(
+            token_program.as_array(),
+            &[[0x0a; 32], [0x0b; 32], [0x0c; 32]],
+            &data,
+        ),
+        Some(KnownInstruction::TokenTransfer {
+            source: [0x0a; 32],
+            destination: [0x0b; 32],
+            authority: [0x0c; 32],
+            amount: 500,
+        })
+    );
+}
+
+#[test]
+fn test_load_account_state() {
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+    let (_, multisig_account, _, transaction_account) =
+        approved_transfer(&recipient, 1_000, 0, |_| {});
+
+    let (header, body) = TransactionHeader::load(&transaction_account.data).unwrap();
+    assert_eq!(
+        header.account_size().unwrap(),
+        transaction_account.data.len()
+    );
+    assert_eq!(body.len(), header.body_size().unwrap());
+
+    // Accounts of another type are rejected.
+    assert!(TransactionHeader::load(&multisig_account.data).is_err());
+    assert!(MultisigHeader::load(&transaction_account.data).is_err());
+
+    // So is data too short for the body its header describes.
+    let truncated = &transaction_account.data[..transaction_account.data.len() - 1];
+    assert!(TransactionHeader::load(truncated).is_err());
+}
+
+#[test]
+fn test_create_multisig_prefunded_address() {
+    let mollusk = mollusk();
+
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_1_account = Account::new(LAMPORTS_PER_SOL, 0, &system_program);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+
+    // Someone sent lamports to the multisig address before it was created.
+    let (multisig, multisig_header) = multisig_header(1, 2, 2);
+    let multisig_account = Account::new(1_000, 0, &system_program);
+
+    // instruction discriminator = 0
+    let mut ser_instruction_data = vec![0];
+    ser_instruction_data.extend_from_slice(bytemuck::bytes_of(&multisig_header));
+    ser_instruction_data.extend_from_slice(bytemuck::cast_slice::<Pubkey, u8>(&[owner_1, owner_2]));
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &ser_instruction_data,
+        vec![
+            AccountMeta::new(owner_1, true),
+            AccountMeta::new(multisig, false),
+            AccountMeta::new_readonly(system_program, false),
+        ],
+    );
+
+    let size = multisig_header.account_size().unwrap();
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (owner_1, owner_1_account),
+            (multisig, multisig_account),
+            (system_program, system_account),
+        ],
+        &[
+            Check::success(),
+            Check::account(&multisig)
+                .owner(&PROGRAM)
+                .space(size)
+                .lamports(mollusk.sysvars.rent.minimum_balance(size))
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_create_transaction_prefunded_address() {
+    let mollusk = mollusk();
+
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_1_account = Account::new(LAMPORTS_PER_SOL, 0, &system_program);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+
+    let (multisig, multisig_header) = multisig_header(1, 2, 2);
+    let multisig_account = multisig_account(&multisig_header, &[owner_1, owner_2]);
+
+    // The transaction address already holds more than the rent-exempt minimum.
+    let tx_index_bytes = u64::to_le_bytes(1);
+    let (transaction, transaction_bump) = Pubkey::find_program_address(
+        &[
+            TRANSACTION_SEED.as_bytes(),
+            multisig.as_ref(),
+            &tx_index_bytes,
+        ],
+        &PROGRAM,
+    );
+    let transaction_account = Account::new(LAMPORTS_PER_SOL, 0, &system_program);
+
+    let transaction_header = TransactionHeader {
+        discriminator: TransactionHeader::DISCRIMINATOR,
+        multisig: multisig.to_bytes(),
+        proposer: [0; 32],
+        rent_payer: [0; 32],
+        program_id: system_program.to_bytes(),
+        num_accounts: u64::to_le_bytes(0),
+        num_signers: u64::to_le_bytes(2),
+        data_len: u64::to_le_bytes(0),
+        status: TransactionStatus::Active as u8,
+        approvals: [0; 8],
+        rejections: [0; 8],
+        index: tx_index_bytes,
+        bump: transaction_bump,
+        proposal_hash: [0; 32],
+        vaults: signing_vaults(&multisig, &[0]),
+        assertions: TransactionAssertions::zeroed(),
+    };
+    let tx_signers = [
+        TransactionSigner {
+            pubkey: owner_1.to_bytes(),
+            signed: 255,
+            rejected: 0,
+        },
+        TransactionSigner {
+            pubkey: owner_2.to_bytes(),
+            signed: 0,
+            rejected: 0,
+        },
+    ];
+
+    // instruction discriminator = 1
+    let mut ser_instruction_data = vec![1];
+    ser_instruction_data.extend_from_slice(bytemuck::bytes_of(&transaction_header));
+    ser_instruction_data
+        .extend_from_slice(bytemuck::cast_slice::<TransactionSigner, u8>(&tx_signers));
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &ser_instruction_data,
+        vec![
+            AccountMeta::new(owner_1, true),
+            AccountMeta::new(transaction, false),
+            AccountMeta::new(multisig, false),
+            AccountMeta::new_readonly(system_program, false),
+        ],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (owner_1, owner_1_account),
+            (transaction, transaction_account),
+            (multisig, multisig_account),
+            (system_program, system_account),
+        ],
+        &[
+            Check::success(),
+            Check::account(&owner_1).lamports(LAMPORTS_PER_SOL).build(),
+            Check::account(&transaction)
+                .owner(&PROGRAM)
+                .space(transaction_header.account_size().unwrap())
+                .lamports(LAMPORTS_PER_SOL)
+                .build(),
+        ],
+    );
+}
+
+/// Sets up a 2-of-2 multisig with a transaction transferring `amount`
+/// lamports from the vault to `recipient`, approved by owner 1 and, if
+/// `owner_2_signed` is 255, by owner 2.
+fn approved_transfer(
+    recipient: &Pubkey,
+    amount: u64,
+    owner_2_signed: u8,
+    configure: impl FnOnce(&mut MultisigHeader),
+) -> (Pubkey, Account, Pubkey, Account) {
+    let (system_program, _) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+
+    let (multisig, mut multisig_header) = multisig_header(1, 2, 2);
+    configure(&mut multisig_header);
+    let (vault, _) = vault(&multisig, 0);
+
+    let data = solana_sdk::system_instruction::transfer(&vault, recipient, amount).data;
+    let (transaction, transaction_header) =
+        transaction_header(2, &multisig, &system_program, 2, 2, data.len() as u64);
+    let tx_accounts = [
+        TransactionAccount {
+            pubkey: vault.to_bytes(),
+            is_signer: 1,
+            is_writable: 1,
+        },
+        TransactionAccount {
+            pubkey: recipient.to_bytes(),
+            is_signer: 0,
+            is_writable: 1,
+        },
+    ];
+    let tx_signers = [
+        TransactionSigner {
+            pubkey: owner_1.to_bytes(),
+            signed: 255,
+            rejected: 0,
+        },
+        TransactionSigner {
+            pubkey: owner_2.to_bytes(),
+            signed: owner_2_signed,
+            rejected: 0,
+        },
+    ];
+
+    (
+        multisig,
+        multisig_account(&multisig_header, &[owner_1, owner_2]),
+        transaction,
+        transaction_account(
+            &transaction_header,
+            &tx_accounts,
+            &tx_signers,
+            &data,
+            u64::from_le_bytes(multisig_header.threshold),
+        ),
+    )
+}
+
+/// Executes an approved transfer of 1000 lamports to `recipient` (passing
+/// `passed_recipient` in its place) with `executor` as signer.
+fn execute_transfer(
+    configure: impl FnOnce(&mut MultisigHeader),
+    executor: &Pubkey,
+    recipient: &Pubkey,
+    passed_recipient: &Pubkey,
+    checks: &[Check],
+) {
+    execute_approved(
+        approved_transfer(recipient, 1_000, 255, configure),
+        executor,
+        passed_recipient,
+        checks,
+    );
+}
+
+/// Executes a transfer set up by [`approved_transfer`] with `executor` as
+/// signer, passing `passed_recipient` as recipient.
+fn execute_approved(
+    (multisig, multisig_account, transaction, transaction_account): (
+        Pubkey,
+        Account,
+        Pubkey,
+        Account,
+    ),
+    executor: &Pubkey,
+    passed_recipient: &Pubkey,
+    checks: &[Check],
+) {
+    let mollusk = mollusk();
+
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+    let (vault, _) = vault(&multisig, 0);
+
+    // instruction discriminator = 3
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &[3],
+        vec![
+            AccountMeta::new_readonly(*executor, true),
+            AccountMeta::new(transaction, false),
+            AccountMeta::new_readonly(multisig, false),
+            AccountMeta::new_readonly(system_program, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(*passed_recipient, false),
+        ],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (*executor, Account::default()),
+            (transaction, transaction_account),
+            (multisig, multisig_account),
+            (system_program, system_account),
+            (vault, Account::new(LAMPORTS_PER_SOL, 0, &system_program)),
+            (*passed_recipient, Account::new(0, 0, &system_program)),
+        ],
+        checks,
+    );
+}
+
+#[test]
+fn test_execute_transaction() {
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+    let (multisig, _) = multisig_header(1, 2, 2);
+    let (transaction, _) = transaction_header(2, &multisig, &Pubkey::default(), 0, 0, 0);
+
+    let status_offset = core::mem::offset_of!(TransactionHeader, status);
+    execute_transfer(
+        |_| {},
+        &owner_1,
+        &recipient,
+        &recipient,
+        &[
+            Check::success(),
+            Check::account(&recipient).lamports(1_000).build(),
+            Check::account(&transaction)
+                .data_slice(status_offset, &[TransactionStatus::Executed as u8])
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_execute_transaction_account_mismatch() {
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+    let attacker = Pubkey::new_from_array([0x06; 32]);
+
+    // The caller swaps the approved recipient for another account.
+    execute_transfer(
+        |_| {},
+        &owner_1,
+        &recipient,
+        &attacker,
+        &[Check::err(ProgramError::Custom(
+            CustomError::AccountMismatch as u32,
+        ))],
+    );
+}
+
+#[test]
+fn test_execute_transaction_forged_accounts() {
+    let mollusk = mollusk();
+
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+
+    let (multisig, multisig_account, transaction, transaction_account) =
+        approved_transfer(&recipient, 1_000, 255, |_| {});
+    let (vault, _) = vault(&multisig, 0);
+
+    // instruction discriminator = 3
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &[3],
+        vec![
+            AccountMeta::new_readonly(owner_1, true),
+            AccountMeta::new(transaction, false),
+            AccountMeta::new_readonly(multisig, false),
+            AccountMeta::new_readonly(system_program, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(recipient, false),
+        ],
+    );
+    let accounts = |transaction_account: Account, multisig_account: Account| {
+        [
+            (owner_1, Account::default()),
+            (transaction, transaction_account),
+            (multisig, multisig_account),
+            (system_program, system_account.clone()),
+            (vault, Account::new(LAMPORTS_PER_SOL, 0, &system_program)),
+            (recipient, Account::new(0, 0, &system_program)),
+        ]
+    };
+
+    // A copy of the transaction owned by another program.
+    let mut forged = transaction_account.clone();
+    forged.owner = system_program;
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &accounts(forged, multisig_account.clone()),
+        &[Check::err(ProgramError::IllegalOwner)],
+    );
+
+    // The multisig passed in place of the transaction.
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &accounts(multisig_account.clone(), multisig_account),
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_execute_policy_any_member() {
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+    let outsider = Pubkey::new_from_array([0x06; 32]);
+
+    execute_transfer(
+        |_| {},
+        &outsider,
+        &recipient,
+        &recipient,
+        &[Check::err(ProgramError::Custom(
+            CustomError::UnauthorizedExecutor as u32,
+        ))],
+    );
+}
+
+/// Executes an approved transfer of 1000 lamports from a vault holding
+/// 1 SOL, carrying `assertions`.
+fn execute_asserted_transfer(assertions: &[TransactionAssertion], checks: &[Check]) {
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+
+    let (multisig, multisig_account, transaction, mut transaction_account) =
+        approved_transfer(&recipient, 1_000, 255, |_| {});
+    let (header, _) = TransactionHeader::load_mut(&mut transaction_account.data).unwrap();
+    header.assertions.num_assertions = assertions.len() as u8;
+    header.assertions.assertions[..assertions.len()].copy_from_slice(assertions);
+
+    execute_approved(
+        (multisig, multisig_account, transaction, transaction_account),
+        &owner_1,
+        &recipient,
+        checks,
+    );
+}
+
+/// Builds an assertion on account `account_index` of a transaction.
+fn assertion(
+    kind: AssertionKind,
+    after: bool,
+    account_index: u8,
+    value: &[u8],
+) -> TransactionAssertion {
+    let mut assertion = TransactionAssertion::zeroed();
+    assertion.kind = kind as u8;
+    assertion.after = if after { 255 } else { 0 };
+    assertion.account_index = account_index;
+    assertion.value[..value.len()].copy_from_slice(value);
+    assertion
+}
+
+#[test]
+fn test_execute_transaction_assertions() {
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+
+    // The vault (account 0) is funded before, the recipient (account 1) paid
+    // after, and its data is unchanged.
+    execute_asserted_transfer(
+        &[
+            assertion(
+                AssertionKind::LamportsAtLeast,
+                false,
+                0,
+                &LAMPORTS_PER_SOL.to_le_bytes(),
+            ),
+            assertion(
+                AssertionKind::LamportsAtLeast,
+                true,
+                1,
+                &1_000u64.to_le_bytes(),
+            ),
+            assertion(
+                AssertionKind::DataHashEquals,
+                true,
+                1,
+                &solana_sdk::hash::hash(&[]).to_bytes(),
+            ),
+        ],
+        &[
+            Check::success(),
+            Check::account(&recipient).lamports(1_000).build(),
+        ],
+    );
+
+    // Execution is aborted if an assertion fails before...
+    execute_asserted_transfer(
+        &[assertion(
+            AssertionKind::LamportsAtLeast,
+            false,
+            0,
+            &(LAMPORTS_PER_SOL + 1).to_le_bytes(),
+        )],
+        &[Check::err(ProgramError::Custom(
+            CustomError::AssertionFailed as u32,
+        ))],
+    );
+
+    // ...or after the instruction is invoked.
+    execute_asserted_transfer(
+        &[assertion(
+            AssertionKind::LamportsAtMost,
+            true,
+            1,
+            &999u64.to_le_bytes(),
+        )],
+        &[Check::err(ProgramError::Custom(
+            CustomError::AssertionFailed as u32,
+        ))],
+    );
+
+    // Token assertions only apply to token accounts.
+    execute_asserted_transfer(
+        &[assertion(AssertionKind::TokenAmountAtMost, false, 1, &[])],
+        &[Check::err(ProgramError::Custom(
+            CustomError::AssertionFailed as u32,
+        ))],
+    );
+}
+
+#[test]
+fn test_execute_policy_executors() {
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+    let executor = Pubkey::new_from_array([0x0a; 32]);
+
+    let configure = |header: &mut MultisigHeader| {
+        header.execute_policy = ExecutePolicy::Executors as u8;
+        header.num_executors = 1;
+        header.executors[0] = executor.to_bytes();
+    };
+
+    execute_transfer(
+        configure,
+        &executor,
+        &recipient,
+        &recipient,
+        &[
+            Check::success(),
+            Check::account(&recipient).lamports(1_000).build(),
+        ],
+    );
+
+    // Owners that are not designated executors cannot execute.
+    execute_transfer(
+        configure,
+        &owner_1,
+        &recipient,
+        &recipient,
+        &[Check::err(ProgramError::Custom(
+            CustomError::UnauthorizedExecutor as u32,
+        ))],
+    );
+}
+
+/// Builds a threshold policy for instructions of `program_id` starting with
+/// `prefix`.
+fn threshold_policy(program_id: &Pubkey, prefix: &[u8], threshold: u64) -> ThresholdPolicy {
+    let mut policy = ThresholdPolicy::zeroed();
+    policy.program_id = program_id.to_bytes();
+    policy.prefix_len = prefix.len() as u8;
+    policy.prefix[..prefix.len()].copy_from_slice(prefix);
+    policy.threshold = u64::to_le_bytes(threshold);
+    policy
+}
+
+#[test]
+fn test_vault_address() {
+    let (multisig, _) = multisig_header(1, 2, 2);
+    for index in [0, 1, 255] {
+        assert_eq!(
+            vault(&multisig, index),
+            Pubkey::find_program_address(
+                &Multisig::vault_seeds(multisig.as_array(), &[index]),
+                &PROGRAM,
+            )
+        );
+    }
+}
+
+#[test]
+fn test_threshold_for() {
+    let (system_program, _) = mollusk_svm::program::keyed_account_for_system_program();
+    let token_program = Pubkey::new_from_array([0x07; 32]);
+    let (_, mut header) = multisig_header(1, 5, 3);
+    header.num_threshold_policies = 3;
+    header.threshold_policies[0] = threshold_policy(&system_program, &[], 4);
+    header.threshold_policies[1] = threshold_policy(&system_program, &[2, 0, 0, 0], 1);
+    header.threshold_policies[2] = threshold_policy(&token_program, &[], 2);
+    assert!(header.validate_threshold_policies().is_ok());
+
+    // The matching policy with the longest prefix applies.
+    assert_eq!(
+        header.threshold_for(system_program.as_array(), &[2, 0, 0, 0, 1]),
+        1
+    );
+    assert_eq!(
+        header.threshold_for(system_program.as_array(), &[0, 0, 0, 0]),
+        4
+    );
+    assert_eq!(header.threshold_for(token_program.as_array(), &[3]), 2);
+
+    // Other programs use the threshold of the multisig.
+    assert_eq!(header.threshold_for(&[0x08; 32], &[]), 3);
+
+    // Changes to the multisig itself require the strictest threshold.
+    assert_eq!(header.threshold_for(&ID, &[]), 4);
+
+    // Policies must be satisfiable by the owners.
+    header.threshold_policies[2].threshold = u64::to_le_bytes(6);
+    assert!(header.validate_threshold_policies().is_err());
+}
+
+#[test]
+fn test_execute_threshold_policy() {
+    let (system_program, _) = mollusk_svm::program::keyed_account_for_system_program();
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+
+    // One approval is enough for the multisig, but transfers need both owners.
+    execute_approved(
+        approved_transfer(&recipient, 1_000, 0, |header| {
+            header.threshold = u64::to_le_bytes(1);
+            header.num_threshold_policies = 1;
+            header.threshold_policies[0] = threshold_policy(&system_program, &[2, 0, 0, 0], 2);
+        }),
+        &owner_1,
+        &recipient,
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+
+    // A policy for other instructions does not apply.
+    execute_approved(
+        approved_transfer(&recipient, 1_000, 0, |header| {
+            header.threshold = u64::to_le_bytes(1);
+            header.num_threshold_policies = 1;
+            header.threshold_policies[0] = threshold_policy(&system_program, &[0, 0, 0, 0], 2);
+        }),
+        &owner_1,
+        &recipient,
+        &[
+            Check::success(),
+            Check::account(&recipient).lamports(1_000).build(),
+        ],
+    );
+}
+
+#[test]
+fn test_program_filter() {
+    let (system_program, _) = mollusk_svm::program::keyed_account_for_system_program();
+    let token_program = Pubkey::new_from_array([0x07; 32]);
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+
+    let filter = |program_filter: ProgramFilter, program: Pubkey| {
+        move |header: &mut MultisigHeader| {
+            header.program_filter = program_filter as u8;
+            header.num_filtered_programs = 1;
+            header.filtered_programs[0] = program.to_bytes();
+        }
+    };
+
+    // Listed programs can be invoked with an allowlist.
+    execute_transfer(
+        filter(ProgramFilter::Allowlist, system_program),
+        &owner_1,
+        &recipient,
+        &recipient,
+        &[
+            Check::success(),
+            Check::account(&recipient).lamports(1_000).build(),
+        ],
+    );
+
+    // Other programs cannot.
+    execute_transfer(
+        filter(ProgramFilter::Allowlist, token_program),
+        &owner_1,
+        &recipient,
+        &recipient,
+        &[Check::err(ProgramError::Custom(
+            CustomError::ProgramNotAllowed as u32,
+        ))],
+    );
+
+    // Listed programs cannot be invoked with a denylist.
+    execute_transfer(
+        filter(ProgramFilter::Denylist, system_program),
+        &owner_1,
+        &recipient,
+        &recipient,
+        &[Check::err(ProgramError::Custom(
+            CustomError::ProgramNotAllowed as u32,
+        ))],
+    );
+}
+
+#[test]
+fn test_set_threshold_policies() {
+    let mollusk = mollusk();
+
+    let (system_program, _) = mollusk_svm::program::keyed_account_for_system_program();
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+
+    let (multisig, mut multisig_header) = multisig_header(1, 2, 2);
+    multisig_header.transaction_index = u64::to_le_bytes(3);
+    let (vault, _) = vault(&multisig, 0);
+
+    let policy = threshold_policy(&system_program, &[2, 0, 0, 0], 1);
+
+    // instruction discriminator = 13
+    let mut ser_instruction_data = vec![13];
+    ser_instruction_data.extend_from_slice(bytemuck::bytes_of(&policy));
+
+    let set_policies = |authority: &Pubkey, checks: &[Check]| {
+        let instruction = Instruction::new_with_bytes(
+            PROGRAM,
+            &ser_instruction_data,
+            vec![
+                AccountMeta::new(multisig, false),
+                AccountMeta::new_readonly(*authority, true),
+            ],
+        );
+        mollusk.process_and_validate_instruction(
+            &instruction,
+            &[
+                (
+                    multisig,
+                    multisig_account(&multisig_header, &[owner_1, owner_2]),
+                ),
+                (*authority, Account::default()),
+            ],
+            checks,
+        );
+    };
+
+    // Policies are changed by the multisig itself, through its first vault.
+    set_policies(
+        &vault,
+        &[
+            Check::success(),
+            Check::account(&multisig)
+                .data_slice(
+                    core::mem::offset_of!(MultisigHeader, stale_transaction_index),
+                    &u64::to_le_bytes(3),
+                )
+                .build(),
+            Check::account(&multisig)
+                .data_slice(
+                    core::mem::offset_of!(MultisigHeader, threshold_policies),
+                    bytemuck::bytes_of(&policy),
+                )
+                .build(),
+        ],
+    );
+
+    // Owners cannot change them directly.
+    set_policies(&owner_1, &[Check::err(ProgramError::IncorrectAuthority)]);
+}
+
+#[test]
+fn test_execute_policy_permissionless() {
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+    let outsider = Pubkey::new_from_array([0x06; 32]);
+
+    execute_transfer(
+        |header| header.execute_policy = ExecutePolicy::Permissionless as u8,
+        &outsider,
+        &recipient,
+        &recipient,
+        &[
+            Check::success(),
+            Check::account(&recipient).lamports(1_000).build(),
+        ],
+    );
+}
+
+/// Sends the reject or revoke instruction `discriminator` from `owner` for a
+/// 2-of-2 transfer already approved by owner 1.
+fn vote_on_transfer(discriminator: u8, owner: &Pubkey, checks: &[Check]) {
+    let mollusk = mollusk();
+
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+    let (multisig, multisig_account, transaction, transaction_account) =
+        approved_transfer(&recipient, 1_000, 0, |_| {});
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &[discriminator],
+        vec![
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new(transaction, false),
+            AccountMeta::new_readonly(multisig, false),
+        ],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (*owner, Account::default()),
+            (transaction, transaction_account),
+            (multisig, multisig_account),
+        ],
+        checks,
+    );
+}
+
+#[test]
+fn test_reject_transaction() {
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+    let (multisig, _) = multisig_header(1, 2, 2);
+    let (transaction, _) = transaction_header(2, &multisig, &Pubkey::default(), 0, 0, 0);
+
+    // With one of two owners rejecting, a 2-of-2 threshold can no longer be met.
+    // instruction discriminator = 9
+    vote_on_transfer(
+        9,
+        &owner_2,
+        &[
+            Check::success(),
+            Check::account(&transaction)
+                .data_slice(
+                    core::mem::offset_of!(TransactionHeader, status),
+                    &[TransactionStatus::Rejected as u8],
+                )
+                .build(),
+            Check::account(&transaction)
+                .data_slice(
+                    core::mem::offset_of!(TransactionHeader, rejections),
+                    &u64::to_le_bytes(1),
+                )
+                .build(),
+        ],
+    );
+
+    // Owners that already approved cannot also reject.
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    vote_on_transfer(
+        9,
+        &owner_1,
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+}
+
+#[test]
+fn test_revoke_vote() {
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+    let (multisig, _) = multisig_header(1, 2, 2);
+    let (transaction, _) = transaction_header(2, &multisig, &Pubkey::default(), 0, 0, 0);
+
+    // instruction discriminator = 10
+    vote_on_transfer(
+        10,
+        &owner_1,
+        &[
+            Check::success(),
+            Check::account(&transaction)
+                .data_slice(
+                    core::mem::offset_of!(TransactionHeader, approvals),
+                    &u64::to_le_bytes(0),
+                )
+                .build(),
+        ],
+    );
+
+    // Owners that have not voted have nothing to revoke.
+    vote_on_transfer(
+        10,
+        &owner_2,
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+}
+
+/// Sets up a 2-of-2 multisig with a draft transaction, proposed by owner 1,
+/// transferring `amount` lamports from the vault to `recipient`.
+fn draft_transfer(recipient: &Pubkey, amount: u64) -> (Pubkey, Account, Pubkey, Account) {
+    let (system_program, _) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+
+    let (multisig, multisig_header) = multisig_header(1, 2, 2);
+    let (vault, _) = vault(&multisig, 0);
+
+    let data = solana_sdk::system_instruction::transfer(&vault, recipient, amount).data;
+    let (transaction, mut transaction_header) =
+        transaction_header(2, &multisig, &system_program, 2, 2, data.len() as u64);
+    transaction_header.status = TransactionStatus::Draft as u8;
+    let tx_accounts = [
+        TransactionAccount {
+            pubkey: vault.to_bytes(),
+            is_signer: 1,
+            is_writable: 1,
+        },
+        TransactionAccount {
+            pubkey: recipient.to_bytes(),
+            is_signer: 0,
+            is_writable: 1,
+        },
+    ];
+    let tx_signers = [
+        TransactionSigner {
+            pubkey: owner_1.to_bytes(),
+            signed: 0,
+            rejected: 0,
+        },
+        TransactionSigner {
+            pubkey: owner_2.to_bytes(),
+            signed: 0,
+            rejected: 0,
+        },
+    ];
+
+    (
+        multisig,
+        multisig_account(&multisig_header, &[owner_1, owner_2]),
+        transaction,
+        transaction_account(&transaction_header, &tx_accounts, &tx_signers, &data, 2),
+    )
+}
+
+/// New content of a draft transfer: a transfer of 2000 lamports that also
+/// lists the memo program.
+fn updated_transfer(
+    multisig: &Pubkey,
+    recipient: &Pubkey,
+) -> (InlineTransactionHeader, [TransactionAccount; 3], Vec<u8>) {
+    let (system_program, _) = mollusk_svm::program::keyed_account_for_system_program();
+    let memo = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+    let (vault, _) = vault(multisig, 0);
+
+    let data = solana_sdk::system_instruction::transfer(&vault, recipient, 2_000).data;
+    let content = InlineTransactionHeader {
+        program_id: system_program.to_bytes(),
+        num_accounts: u64::to_le_bytes(3),
+        data_len: u64::to_le_bytes(data.len() as u64),
+        vaults: signing_vaults(multisig, &[0]),
+        assertions: TransactionAssertions::zeroed(),
+    };
+    let tx_accounts = [
+        TransactionAccount {
+            pubkey: vault.to_bytes(),
+            is_signer: 1,
+            is_writable: 1,
+        },
+        TransactionAccount {
+            pubkey: recipient.to_bytes(),
+            is_signer: 0,
+            is_writable: 1,
+        },
+        TransactionAccount {
+            pubkey: memo.to_bytes(),
+            is_signer: 0,
+            is_writable: 0,
+        },
+    ];
+
+    (content, tx_accounts, data)
+}
+
+/// Rewrites a draft transfer with [`updated_transfer`] as `proposer`, the
+/// rent of the draft having been paid by `rent_payer`. `settled_by` is passed
+/// as the rent payer unless it is the proposer.
+fn update_draft_transfer(
+    proposer: &Pubkey,
+    rent_payer: &Pubkey,
+    settled_by: &Pubkey,
+    checks: &[Check],
+) {
+    let mollusk = mollusk();
+
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+    let (multisig, multisig_account, transaction, mut transaction_account) =
+        draft_transfer(&recipient, 1_000);
+    let rent_payer_offset = core::mem::offset_of!(TransactionHeader, rent_payer);
+    transaction_account.data[rent_payer_offset..rent_payer_offset + 32]
+        .copy_from_slice(rent_payer.as_ref());
+    let (content, tx_accounts, data) = updated_transfer(&multisig, &recipient);
+
+    // instruction discriminator = 11
+    let mut instruction_data = vec![11];
+    instruction_data.extend_from_slice(bytemuck::bytes_of(&content));
+    instruction_data
+        .extend_from_slice(bytemuck::cast_slice::<TransactionAccount, u8>(&tx_accounts));
+    instruction_data.extend_from_slice(&data);
+    let mut account_metas = vec![
+        AccountMeta::new(*proposer, true),
+        AccountMeta::new(transaction, false),
+        AccountMeta::new_readonly(multisig, false),
+        AccountMeta::new_readonly(system_program, false),
+    ];
+    let mut accounts = vec![
+        (
+            *proposer,
+            Account::new(LAMPORTS_PER_SOL, 0, &system_program),
+        ),
+        (transaction, transaction_account),
+        (multisig, multisig_account),
+        (system_program, system_account),
+    ];
+    if settled_by != proposer {
+        account_metas.push(AccountMeta::new(*settled_by, false));
+        accounts.push((
+            *settled_by,
+            Account::new(LAMPORTS_PER_SOL, 0, &system_program),
+        ));
+    }
+    // The writable accounts of the transaction.
+    for account in tx_accounts
+        .iter()
+        .filter(|account| account.is_writable != 0)
+    {
+        let pubkey = Pubkey::new_from_array(account.pubkey);
+        account_metas.push(AccountMeta::new(pubkey, false));
+        accounts.push((pubkey, Account::default()));
+    }
+    let instruction = Instruction::new_with_bytes(PROGRAM, &instruction_data, account_metas);
+
+    mollusk.process_and_validate_instruction(&instruction, &accounts, checks);
+}
+
+#[test]
+fn test_update_draft_transaction() {
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+    let (multisig, _) = multisig_header(1, 2, 2);
+    let (transaction, _) = transaction_header(2, &multisig, &Pubkey::default(), 0, 0, 0);
+
+    let (content, tx_accounts, data) = updated_transfer(&multisig, &recipient);
+    let proposal_hash = Transaction::proposal_hash(
+        &content.program_id,
+        &content.vaults,
+        &content.assertions,
+        &tx_accounts,
+        &data,
+    );
+
+    // The account is resized to fit the new content, which stays a draft.
+    update_draft_transfer(
+        &owner_1,
+        &owner_1,
+        &owner_1,
+        &[
+            Check::success(),
+            Check::account(&transaction)
+                .space(
+                    transaction_header(2, &multisig, &Pubkey::default(), 3, 2, data.len() as u64)
+                        .1
+                        .account_size()
+                        .unwrap(),
+                )
+                .data_slice(
+                    core::mem::offset_of!(TransactionHeader, proposal_hash),
+                    &proposal_hash,
+                )
+                .build(),
+            Check::account(&transaction)
+                .data_slice(
+                    core::mem::offset_of!(TransactionHeader, status),
+                    &[TransactionStatus::Draft as u8],
+                )
+                .build(),
+        ],
+    );
+
+    // Only the proposer can edit the draft.
+    update_draft_transfer(
+        &owner_2,
+        &owner_1,
+        &owner_2,
+        &[Check::err(ProgramError::IncorrectAuthority)],
+    );
+}
+
+#[test]
+fn test_update_draft_transaction_rent_payer() {
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let relayer = Pubkey::new_from_array([0x09; 32]);
+    let (multisig, _) = multisig_header(1, 2, 2);
+    let (transaction, header) = transaction_header(2, &multisig, &Pubkey::default(), 3, 2, 12);
+    let rent = mollusk()
+        .sysvars
+        .rent
+        .minimum_balance(header.account_size().unwrap());
+
+    // The excess rent of a draft paid for by a relayer goes back to it...
+    update_draft_transfer(
+        &owner_1,
+        &relayer,
+        &relayer,
+        &[
+            Check::success(),
+            Check::account(&transaction).lamports(rent).build(),
+            Check::account(&relayer)
+                .lamports(2 * LAMPORTS_PER_SOL - rent)
+                .build(),
+        ],
+    );
+
+    // ...which must be passed to settle it.
+    update_draft_transfer(
+        &owner_1,
+        &relayer,
+        &owner_1,
+        &[Check::err(ProgramError::Custom(
+            CustomError::AccountMismatch as u32,
+        ))],
+    );
+}
+
+#[test]
+fn test_activate_transaction() {
+    let mollusk = mollusk();
+
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+    let (multisig, multisig_account, transaction, transaction_account) =
+        draft_transfer(&recipient, 1_000);
+
+    // Drafts are not open to votes.
+    // instruction discriminator = 2
+    let approve = Instruction::new_with_bytes(
+        PROGRAM,
+        &[2],
+        vec![
+            AccountMeta::new_readonly(owner_2, true),
+            AccountMeta::new(transaction, false),
+            AccountMeta::new_readonly(multisig, false),
+            AccountMeta::new_readonly(system_program, false),
+        ],
+    );
+    mollusk.process_and_validate_instruction(
+        &approve,
+        &[
+            (owner_2, Account::default()),
+            (transaction, transaction_account.clone()),
+            (multisig, multisig_account.clone()),
+            (system_program, system_account),
+        ],
+        &[Check::err(ProgramError::Custom(
+            CustomError::InvalidTransactionStatus as u32,
+        ))],
+    );
+
+    // Activation opens the draft to votes with the proposer's approval.
+    // instruction discriminator = 12
+    let activate = Instruction::new_with_bytes(
+        PROGRAM,
+        &[12],
+        vec![
+            AccountMeta::new_readonly(owner_1, true),
+            AccountMeta::new(transaction, false),
+            AccountMeta::new_readonly(multisig, false),
+        ],
+    );
+    mollusk.process_and_validate_instruction(
+        &activate,
+        &[
+            (owner_1, Account::default()),
+            (transaction, transaction_account),
+            (multisig, multisig_account),
+        ],
+        &[
+            Check::success(),
+            Check::account(&transaction)
+                .data_slice(
+                    core::mem::offset_of!(TransactionHeader, status),
+                    &[TransactionStatus::Active as u8],
+                )
+                .build(),
+            Check::account(&transaction)
+                .data_slice(
+                    core::mem::offset_of!(TransactionHeader, approvals),
+                    &u64::to_le_bytes(1),
+                )
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_cancel_transaction() {
+    let mollusk = mollusk();
+
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+    let (multisig, multisig_account, transaction, transaction_account) =
+        draft_transfer(&recipient, 1_000);
+
+    // instruction discriminator = 15
+    let cancel = |owner: Pubkey| {
+        Instruction::new_with_bytes(
+            PROGRAM,
+            &[15],
+            vec![
+                AccountMeta::new_readonly(owner, true),
+                AccountMeta::new(transaction, false),
+                AccountMeta::new_readonly(multisig, false),
+            ],
+        )
+    };
+
+    // Only the proposer can cancel the transaction...
+    mollusk.process_and_validate_instruction(
+        &cancel(owner_2),
+        &[
+            (owner_2, Account::default()),
+            (transaction, transaction_account.clone()),
+            (multisig, multisig_account.clone()),
+        ],
+        &[Check::err(ProgramError::IncorrectAuthority)],
+    );
+    let result = mollusk.process_and_validate_instruction(
+        &cancel(owner_1),
+        &[
+            (owner_1, Account::default()),
+            (transaction, transaction_account),
+            (multisig, multisig_account.clone()),
+        ],
+        &[
+            Check::success(),
+            Check::account(&transaction)
+                .data_slice(
+                    core::mem::offset_of!(TransactionHeader, status),
+                    &[TransactionStatus::Cancelled as u8],
+                )
+                .build(),
+        ],
+    );
+    let transaction_account = result.get_account(&transaction).unwrap().clone();
+
+    // ...which is then closed to votes.
+    // instruction discriminator = 2
+    let approve = Instruction::new_with_bytes(
+        PROGRAM,
+        &[2],
+        vec![
+            AccountMeta::new_readonly(owner_2, true),
+            AccountMeta::new(transaction, false),
+            AccountMeta::new_readonly(multisig, false),
+            AccountMeta::new_readonly(system_program, false),
+        ],
+    );
+    mollusk.process_and_validate_instruction(
+        &approve,
+        &[
+            (owner_2, Account::default()),
+            (transaction, transaction_account),
+            (multisig, multisig_account),
+            (system_program, system_account),
+        ],
+        &[Check::err(ProgramError::Custom(
+            CustomError::InvalidTransactionStatus as u32,
+        ))],
+    );
+}
+
+/// Sends `ApproveAndExecute` from owner 2 for a transfer of 1000 lamports to
+/// `recipient` already approved by owner 1.
+fn approve_and_execute_transfer(
+    configure: impl FnOnce(&mut MultisigHeader),
+    recipient: &Pubkey,
+    checks: &[Check],
+) {
+    let mollusk = mollusk();
+
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+
+    // Owner 1 already approved, owner 2 is the final signer.
+    let (multisig, multisig_account, transaction, transaction_account) =
+        approved_transfer(recipient, 1_000, 0, configure);
+    let (vault, _) = vault(&multisig, 0);
+
+    // instruction discriminator = 6
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &[6],
+        vec![
+            AccountMeta::new_readonly(owner_2, true),
+            AccountMeta::new(transaction, false),
+            AccountMeta::new_readonly(multisig, false),
+            AccountMeta::new_readonly(system_program, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(*recipient, false),
+        ],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (owner_2, Account::default()),
+            (transaction, transaction_account),
+            (multisig, multisig_account),
+            (system_program, system_account),
+            (vault, Account::new(LAMPORTS_PER_SOL, 0, &system_program)),
+            (*recipient, Account::new(0, 0, &system_program)),
+        ],
+        checks,
+    );
+}
+
+#[test]
+fn test_approve_and_execute() {
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+    let (multisig, _) = multisig_header(1, 2, 2);
+    let (transaction, _) = transaction_header(2, &multisig, &Pubkey::default(), 0, 0, 0);
+
+    let status_offset = core::mem::offset_of!(TransactionHeader, status);
+    approve_and_execute_transfer(
+        |_| {},
+        &recipient,
+        &[
+            Check::success(),
+            Check::account(&recipient).lamports(1_000).build(),
+            Check::account(&transaction)
+                .data_slice(status_offset, &[TransactionStatus::Executed as u8])
+                .build(),
+        ],
+    );
+
+    // A final approver who is not an executor only records the approval.
+    approve_and_execute_transfer(
+        |header| {
+            header.execute_policy = ExecutePolicy::Executors as u8;
+            header.num_executors = 1;
+            header.executors[0] = owner_1.to_bytes();
+        },
+        &recipient,
+        &[
+            Check::success(),
+            Check::account(&recipient).lamports(0).build(),
+            Check::account(&transaction)
+                .data_slice(status_offset, &[TransactionStatus::Approved as u8])
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_approve_and_execute_below_threshold() {
+    let mollusk = mollusk();
+
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+    let owner_3 = Pubkey::new_from_array([0x03; 32]);
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+
+    // 3-of-3 multisig: the second approval only records the approval.
+    let (multisig, multisig_header) = multisig_header(1, 3, 3);
+    let (vault, _) = vault(&multisig, 0);
+    let data = solana_sdk::system_instruction::transfer(&vault, &recipient, 1_000).data;
+    let (transaction, transaction_header) =
+        transaction_header(2, &multisig, &system_program, 2, 3, data.len() as u64);
+    let tx_accounts = [
+        TransactionAccount {
+            pubkey: vault.to_bytes(),
+            is_signer: 1,
+            is_writable: 1,
+        },
+        TransactionAccount {
+            pubkey: recipient.to_bytes(),
+            is_signer: 0,
+            is_writable: 1,
+        },
+    ];
+    let tx_signers = [
+        TransactionSigner {
+            pubkey: owner_1.to_bytes(),
+            signed: 255,
+            rejected: 0,
+        },
+        TransactionSigner {
+            pubkey: owner_2.to_bytes(),
+            signed: 0,
+            rejected: 0,
+        },
+        TransactionSigner {
+            pubkey: owner_3.to_bytes(),
+            signed: 0,
+            rejected: 0,
+        },
+    ];
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &[6],
+        vec![
+            AccountMeta::new_readonly(owner_2, true),
+            AccountMeta::new(transaction, false),
+            AccountMeta::new_readonly(multisig, false),
+            AccountMeta::new_readonly(system_program, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(recipient, false),
+        ],
+    );
+
+    let status_offset = core::mem::offset_of!(TransactionHeader, status);
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (owner_2, Account::default()),
+            (
+                transaction,
+                transaction_account(&transaction_header, &tx_accounts, &tx_signers, &data, 3),
+            ),
+            (
+                multisig,
+                multisig_account(&multisig_header, &[owner_1, owner_2, owner_3]),
+            ),
+            (system_program, system_account),
+            (vault, Account::new(LAMPORTS_PER_SOL, 0, &system_program)),
+            (recipient, Account::new(0, 0, &system_program)),
+        ],
+        &[
+            Check::success(),
+            Check::account(&recipient).lamports(0).build(),
+            Check::account(&transaction)
+                .data_slice(status_offset, &[TransactionStatus::Active as u8])
+                .build(),
+        ],
+    );
+}
+
+/// Runs `ExecuteInline` for a transfer of 1000 lamports from vault
+/// `vault_index` on a 2-owner multisig with the given threshold.
+fn execute_inline_transfer(threshold: u64, vault_index: u8, checks: &[Check]) {
+    let mollusk = mollusk();
+
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+
+    let (multisig, multisig_header) = multisig_header(1, 2, threshold);
+    let (vault, _) = vault(&multisig, vault_index);
+
+    let data = solana_sdk::system_instruction::transfer(&vault, &recipient, 1_000).data;
+    let inline_header = InlineTransactionHeader {
+        program_id: system_program.to_bytes(),
+        num_accounts: u64::to_le_bytes(2),
+        data_len: u64::to_le_bytes(data.len() as u64),
+        vaults: signing_vaults(&multisig, &[vault_index]),
+        assertions: TransactionAssertions::zeroed(),
+    };
+    let tx_accounts = [
+        TransactionAccount {
+            pubkey: vault.to_bytes(),
+            is_signer: 1,
+            is_writable: 1,
+        },
+        TransactionAccount {
+            pubkey: recipient.to_bytes(),
+            is_signer: 0,
+            is_writable: 1,
+        },
+    ];
+
+    // instruction discriminator = 7
+    let mut ser_instruction_data = vec![7];
+    ser_instruction_data.extend_from_slice(bytemuck::bytes_of(&inline_header));
+    ser_instruction_data
+        .extend_from_slice(bytemuck::cast_slice::<TransactionAccount, u8>(&tx_accounts));
+    ser_instruction_data.extend_from_slice(&data);
+
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &ser_instruction_data,
+        vec![
+            AccountMeta::new_readonly(owner_1, true),
+            AccountMeta::new_readonly(multisig, false),
+            AccountMeta::new_readonly(system_program, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(recipient, false),
+        ],
+    );
+
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (owner_1, Account::default()),
+            (
+                multisig,
+                multisig_account(&multisig_header, &[owner_1, owner_2]),
+            ),
+            (system_program, system_account),
+            (vault, Account::new(LAMPORTS_PER_SOL, 0, &system_program)),
+            (recipient, Account::new(0, 0, &system_program)),
+        ],
+        checks,
+    );
+}
+
+#[test]
+fn test_execute_inline() {
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+
+    execute_inline_transfer(
+        1,
+        0,
+        &[
+            Check::success(),
+            Check::account(&recipient).lamports(1_000).build(),
+        ],
+    );
+}
+
+#[test]
+fn test_execute_from_sub_account_vault() {
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+
+    execute_inline_transfer(
+        1,
+        3,
+        &[
+            Check::success(),
+            Check::account(&recipient).lamports(1_000).build(),
+            Check::account(&vault(&multisig_header(1, 2, 1).0, 3).0)
+                .lamports(LAMPORTS_PER_SOL - 1_000)
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_execute_inline_requires_threshold_one() {
+    execute_inline_transfer(
+        2,
+        0,
+        &[Check::err(ProgramError::Custom(
+            CustomError::NotEnoughSigners as u32,
+        ))],
+    );
+}
+
+/// Builds ed25519 program data verifying `message` signed by each keypair,
+/// with every offset pointing into the instruction itself.
+fn ed25519_instruction_data(keypairs: &[&Keypair], message: &[u8]) -> Vec<u8> {
+    let header_size = 2 + 14 * keypairs.len();
+    let message_offset = header_size + 96 * keypairs.len();
+
+    let mut data = vec![keypairs.len() as u8, 0];
+    for index in 0..keypairs.len() {
+        let public_key_offset = (header_size + 96 * index) as u16;
+        let signature_offset = public_key_offset + 32;
+        for value in [
+            signature_offset,
+            u16::MAX,
+            public_key_offset,
+            u16::MAX,
+            message_offset as u16,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    for keypair in keypairs {
+        data.extend_from_slice(keypair.pubkey().as_ref());
+        data.extend_from_slice(keypair.sign_message(message).as_ref());
+    }
+    data.extend_from_slice(message);
+    data
+}
+
+#[test]
+fn test_approve_with_signatures() {
+    let mollusk = mollusk();
+
+    let (system_program, _) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_2 = Keypair::new();
+    let owner_3 = Keypair::new();
+    let owners = [owner_1, owner_2.pubkey(), owner_3.pubkey()];
+
+    let (multisig, multisig_header) = multisig_header(1, 3, 3);
+
+    let tx_data = [1u8, 2, 3, 4];
+    let (transaction, transaction_header) =
+        transaction_header(2, &multisig, &system_program, 0, 3, tx_data.len() as u64);
+    let tx_signers = [
+        TransactionSigner {
+            pubkey: owner_1.to_bytes(),
+            signed: 255,
+            rejected: 0,
+        },
+        TransactionSigner {
+            pubkey: owner_2.pubkey().to_bytes(),
+            signed: 0,
+            rejected: 0,
+        },
+        TransactionSigner {
+            pubkey: owner_3.pubkey().to_bytes(),
+            signed: 0,
+            rejected: 0,
+        },
+    ];
+
+    // Both remaining owners sign the approval message off-chain.
+    let proposal_hash = Transaction::proposal_hash(
+        &system_program.to_bytes(),
+        &transaction_header.vaults,
+        &transaction_header.assertions,
+        &[],
+        &tx_data,
+    );
+    let message = Transaction::approval_message(transaction.as_array(), &proposal_hash);
+    let ed25519_data = ed25519_instruction_data(&[&owner_2, &owner_3], &message);
+
+    // instruction discriminator = 8
+    let instruction = Instruction::new_with_bytes(
+        PROGRAM,
+        &[8],
+        vec![
+            AccountMeta::new(transaction, false),
+            AccountMeta::new_readonly(multisig, false),
+            AccountMeta::new_readonly(instructions_sysvar::ID, false),
+        ],
+    );
+
+    // The signature verification instruction precedes the approval.
+    let ed25519_program = solana_sdk::ed25519_program::ID;
+    let mut sysvar_data = construct_instructions_data(&[
+        BorrowedInstruction {
+            program_id: &ed25519_program,
+            accounts: vec![],
+            data: &ed25519_data,
+        },
+        BorrowedInstruction {
+            program_id: &PROGRAM,
+            accounts: vec![
+                BorrowedAccountMeta {
+                    pubkey: &transaction,
+                    is_signer: false,
+                    is_writable: true,
+                },
+                BorrowedAccountMeta {
+                    pubkey: &multisig,
+                    is_signer: false,
+                    is_writable: false,
+                },
+                BorrowedAccountMeta {
+                    pubkey: &instructions_sysvar::ID,
+                    is_signer: false,
+                    is_writable: false,
+                },
+            ],
+            data: &[8],
+        },
+    ]);
+    store_current_index_checked(&mut sysvar_data, 1).unwrap();
+    let mut sysvar_account = Account::new(LAMPORTS_PER_SOL, sysvar_data.len(), &sysvar::ID);
+    sysvar_account.data = sysvar_data;
+
+    let signed_offset = core::mem::size_of::<TransactionHeader>()
+        + core::mem::offset_of!(TransactionSigner, signed);
+    let signer_size = core::mem::size_of::<TransactionSigner>();
+    mollusk.process_and_validate_instruction(
+        &instruction,
+        &[
+            (
+                transaction,
+                transaction_account(&transaction_header, &[], &tx_signers, &tx_data, 3),
+            ),
+            (multisig, multisig_account(&multisig_header, &owners)),
+            (instructions_sysvar::ID, sysvar_account),
+        ],
+        &[
+            Check::success(),
+            Check::account(&transaction)
+                .data_slice(signed_offset + signer_size, &[255])
+                .build(),
+            Check::account(&transaction)
+                .data_slice(signed_offset + 2 * signer_size, &[255])
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_approve_transaction_with_proposal_hash() {
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+
+    let (multisig, multisig_header) = multisig_header(1, 2, 2);
+    let tx_data = [1u8, 2, 3, 4];
+    let (transaction, transaction_header) =
+        transaction_header(2, &multisig, &system_program, 0, 2, tx_data.len() as u64);
+    let tx_signers = [
+        TransactionSigner {
+            pubkey: owner_1.to_bytes(),
+            signed: 255,
+            rejected: 0,
+        },
+        TransactionSigner {
+            pubkey: owner_2.to_bytes(),
+            signed: 0,
+            rejected: 0,
+        },
+    ];
+    let proposal_hash = Transaction::proposal_hash(
+        &system_program.to_bytes(),
+        &transaction_header.vaults,
+        &transaction_header.assertions,
+        &[],
+        &tx_data,
+    );
+
+    let approve = |expected_hash: &[u8; 32], checks: &[Check]| {
+        // instruction discriminator = 2, followed by the expected hash.
+        let mut ser_instruction_data = vec![2];
+        ser_instruction_data.extend_from_slice(expected_hash);
+
+        let instruction = Instruction::new_with_bytes(
+            PROGRAM,
+            &ser_instruction_data,
+            vec![
+                AccountMeta::new_readonly(owner_2, true),
+                AccountMeta::new(transaction, false),
+                AccountMeta::new_readonly(multisig, false),
+                AccountMeta::new_readonly(system_program, false),
+            ],
+        );
+
+        mollusk().process_and_validate_instruction(
+            &instruction,
+            &[
+                (owner_2, Account::default()),
+                (
+                    transaction,
+                    transaction_account(&transaction_header, &[], &tx_signers, &tx_data, 2),
+                ),
+                (
+                    multisig,
+                    multisig_account(&multisig_header, &[owner_1, owner_2]),
+                ),
+                (system_program, system_account.clone()),
+            ],
+            checks,
+        );
+    };
+
+    approve(&proposal_hash, &[Check::success()]);
+    approve(
+        &[0xff; 32],
+        &[Check::err(ProgramError::Custom(
+            CustomError::ProposalHashMismatch as u32,
+        ))],
+    );
+}
+
+/// Sets up a 2-of-2 board multisig owned by a key and by `engineering_vault`,
+/// with a transaction transferring 1000 lamports from the board vault to
+/// `recipient`, approved by the key and, if `vault_signed` is 255, by the
+/// engineering vault.
+fn board_transfer(
+    engineering_vault: &Pubkey,
+    recipient: &Pubkey,
+    vault_signed: u8,
+) -> (Pubkey, Account, Pubkey, Account) {
+    let (system_program, _) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let board_owner = Pubkey::new_from_array([0x0b; 32]);
+
+    let (board, board_header) = multisig_header(11, 2, 2);
+    let (board_vault, _) = vault(&board, 0);
+
+    let data = solana_sdk::system_instruction::transfer(&board_vault, recipient, 1_000).data;
+    let (transaction, transaction_header) =
+        transaction_header(21, &board, &system_program, 2, 2, data.len() as u64);
+    let tx_accounts = [
+        TransactionAccount {
+            pubkey: board_vault.to_bytes(),
+            is_signer: 1,
+            is_writable: 1,
+        },
+        TransactionAccount {
+            pubkey: recipient.to_bytes(),
+            is_signer: 0,
+            is_writable: 1,
+        },
+    ];
+    let tx_signers = [
+        TransactionSigner {
+            pubkey: board_owner.to_bytes(),
+            signed: 255,
+            rejected: 0,
+        },
+        TransactionSigner {
+            pubkey: engineering_vault.to_bytes(),
+            signed: vault_signed,
+            rejected: 0,
+        },
+    ];
+
+    (
+        board,
+        multisig_account(&board_header, &[board_owner, *engineering_vault]),
+        transaction,
+        transaction_account(&transaction_header, &tx_accounts, &tx_signers, &data, 2),
+    )
+}
+
+/// Executes a transaction of a 1-of-1 engineering multisig that invokes this
+/// program with `data`, signed by engineering vault 0. `accounts` are the
+/// keyed accounts for `tx_accounts`, in order.
+fn execute_as_engineering(
+    data: &[u8],
+    tx_accounts: &[TransactionAccount],
+    accounts: &[(Pubkey, Account)],
+    checks: &[Check],
+) {
+    let mollusk = mollusk();
+
+    let engineering_owner = Pubkey::new_from_array([0x0a; 32]);
+
+    let (engineering, engineering_header) = multisig_header(10, 1, 1);
+    let (transaction, transaction_header) = transaction_header(
+        20,
+        &engineering,
+        &PROGRAM,
+        tx_accounts.len() as u64,
+        1,
+        data.len() as u64,
+    );
+    let tx_signers = [TransactionSigner {
+        pubkey: engineering_owner.to_bytes(),
+        signed: 255,
+        rejected: 0,
+    }];
+
+    let mut account_metas = vec![
+        AccountMeta::new_readonly(engineering_owner, true),
+        AccountMeta::new(transaction, false),
+        AccountMeta::new_readonly(engineering, false),
+        AccountMeta::new_readonly(PROGRAM, false),
+    ];
+    account_metas.extend(tx_accounts.iter().map(|account| {
+        let pubkey = Pubkey::new_from_array(account.pubkey);
+        if account.is_writable != 0 {
+            AccountMeta::new(pubkey, false)
+        } else {
+            AccountMeta::new_readonly(pubkey, false)
+        }
+    }));
+
+    let mut keyed_accounts = vec![
+        (engineering_owner, Account::default()),
+        (
+            transaction,
+            transaction_account(&transaction_header, tx_accounts, &tx_signers, data, 1),
+        ),
+        (
+            engineering,
+            multisig_account(&engineering_header, &[engineering_owner]),
+        ),
+        (
+            PROGRAM,
+            mollusk_svm::program::create_program_account_loader_v3(&PROGRAM),
+        ),
+    ];
+    keyed_accounts.extend_from_slice(accounts);
+
+    // instruction discriminator = 3
+    let instruction = Instruction::new_with_bytes(PROGRAM, &[3], account_metas);
+
+    mollusk.process_and_validate_instruction(&instruction, &keyed_accounts, checks);
+}
+
+#[test]
+fn test_nested_multisig_create_transaction() {
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let board_owner = Pubkey::new_from_array([0x0b; 32]);
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+    let (engineering_vault, _) = vault(&multisig_header(10, 1, 1).0, 0);
+
+    let (board, board_account, _, _) = board_transfer(&engineering_vault, &recipient, 0);
+    let (board_transaction, board_transaction_header) =
+        transaction_header(1, &board, &system_program, 0, 2, 4);
+    let board_signers = [
+        TransactionSigner {
+            pubkey: board_owner.to_bytes(),
+            signed: 0,
+            rejected: 0,
+        },
+        TransactionSigner {
+            pubkey: engineering_vault.to_bytes(),
+            signed: 255,
+            rejected: 0,
+        },
+    ];
+
+    // CreateTransaction on the board, proposed and paid for by the vault.
+    let mut data = vec![1];
+    data.extend_from_slice(bytemuck::bytes_of(&board_transaction_header));
+    data.extend_from_slice(bytemuck::cast_slice::<TransactionSigner, u8>(
+        &board_signers,
+    ));
+    data.extend_from_slice(&[1, 2, 3, 4]);
+    let tx_accounts = [
+        TransactionAccount {
+            pubkey: engineering_vault.to_bytes(),
+            is_signer: 1,
+            is_writable: 1,
+        },
+        TransactionAccount {
+            pubkey: board_transaction.to_bytes(),
+            is_signer: 0,
+            is_writable: 1,
+        },
+        TransactionAccount {
+            pubkey: board.to_bytes(),
+            is_signer: 0,
+            is_writable: 1,
+        },
+        TransactionAccount {
+            pubkey: system_program.to_bytes(),
+            is_signer: 0,
+            is_writable: 0,
+        },
+    ];
+
+    let signers_offset = core::mem::size_of::<TransactionHeader>();
+    let transaction_index_offset = core::mem::offset_of!(MultisigHeader, transaction_index);
+    execute_as_engineering(
+        &data,
+        &tx_accounts,
+        &[
+            (
+                engineering_vault,
+                Account::new(LAMPORTS_PER_SOL, 0, &system_program),
+            ),
+            (board_transaction, Account::new(0, 0, &system_program)),
+            (board, board_account),
+            (system_program, system_account),
+        ],
+        &[
+            Check::success(),
+            Check::account(&board_transaction)
+                .owner(&PROGRAM)
+                .data_slice(
+                    signers_offset,
+                    bytemuck::cast_slice::<TransactionSigner, u8>(&board_signers),
+                )
+                .build(),
+            Check::account(&board)
+                .data_slice(transaction_index_offset, &u64::to_le_bytes(1))
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_nested_multisig_approve() {
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+    let (engineering_vault, _) = vault(&multisig_header(10, 1, 1).0, 0);
+
+    let (board, board_account, board_transaction, board_transaction_account) =
+        board_transfer(&engineering_vault, &recipient, 0);
+
+    // ApproveTransaction on the board, signed by the vault.
+    let tx_accounts = [
+        TransactionAccount {
+            pubkey: engineering_vault.to_bytes(),
+            is_signer: 1,
+            is_writable: 0,
+        },
+        TransactionAccount {
+            pubkey: board_transaction.to_bytes(),
+            is_signer: 0,
+            is_writable: 1,
+        },
+        TransactionAccount {
+            pubkey: board.to_bytes(),
+            is_signer: 0,
+            is_writable: 0,
+        },
+        TransactionAccount {
+            pubkey: system_program.to_bytes(),
+            is_signer: 0,
+            is_writable: 0,
+        },
+    ];
+
+    // The vault is the second signer of the board transfer.
+    let vault_signed_offset = core::mem::size_of::<TransactionHeader>()
+        + 2 * core::mem::size_of::<TransactionAccount>()
+        + core::mem::offset_of!(TransactionSigner, signed)
+        + core::mem::size_of::<TransactionSigner>();
+    execute_as_engineering(
+        &[2],
+        &tx_accounts,
+        &[
+            (engineering_vault, Account::new(0, 0, &system_program)),
+            (board_transaction, board_transaction_account),
+            (board, board_account),
+            (system_program, system_account),
+        ],
+        &[
+            Check::success(),
+            Check::account(&board_transaction)
+                .data_slice(vault_signed_offset, &[255])
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_nested_multisig_execute() {
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+    let (engineering_vault, _) = vault(&multisig_header(10, 1, 1).0, 0);
+
+    let (board, board_account, board_transaction, board_transaction_account) =
+        board_transfer(&engineering_vault, &recipient, 255);
+    let (board_vault, _) = vault(&board, 0);
+
+    // ExecuteTransaction on the board, with the vault as executor.
+    let tx_accounts = [
+        TransactionAccount {
+            pubkey: engineering_vault.to_bytes(),
+            is_signer: 1,
+            is_writable: 0,
+        },
+        TransactionAccount {
+            pubkey: board_transaction.to_bytes(),
+            is_signer: 0,
+            is_writable: 1,
+        },
+        TransactionAccount {
+            pubkey: board.to_bytes(),
+            is_signer: 0,
+            is_writable: 0,
+        },
+        TransactionAccount {
+            pubkey: system_program.to_bytes(),
+            is_signer: 0,
+            is_writable: 0,
+        },
+        TransactionAccount {
+            pubkey: board_vault.to_bytes(),
+            is_signer: 0,
+            is_writable: 1,
+        },
+        TransactionAccount {
+            pubkey: recipient.to_bytes(),
+            is_signer: 0,
+            is_writable: 1,
+        },
+    ];
+
+    let status_offset = core::mem::offset_of!(TransactionHeader, status);
+    execute_as_engineering(
+        &[3],
+        &tx_accounts,
+        &[
+            (engineering_vault, Account::new(0, 0, &system_program)),
+            (board_transaction, board_transaction_account),
+            (board, board_account),
+            (system_program, system_account),
+            (
+                board_vault,
+                Account::new(LAMPORTS_PER_SOL, 0, &system_program),
+            ),
+            (recipient, Account::new(0, 0, &system_program)),
+        ],
+        &[
+            Check::success(),
+            Check::account(&recipient).lamports(1_000).build(),
+            Check::account(&board_transaction)
+                .data_slice(status_offset, &[TransactionStatus::Executed as u8])
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_nested_multisig_execute_lends_no_vault() {
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+    let (engineering_vault, _) = vault(&multisig_header(10, 1, 1).0, 0);
+
+    // An approved board transaction that spends from the engineering vault,
+    // whatever the program filter of the engineering multisig.
+    let (board, board_account, _, _) = board_transfer(&engineering_vault, &recipient, 255);
+    let data = solana_sdk::system_instruction::transfer(&engineering_vault, &recipient, 1_000).data;
+    let (board_transaction, board_transaction_header) =
+        transaction_header(22, &board, &system_program, 2, 2, data.len() as u64);
+    let board_accounts = [
+        TransactionAccount {
+            pubkey: engineering_vault.to_bytes(),
+            is_signer: 1,
+            is_writable: 1,
+        },
+        writable(&recipient),
+    ];
+    let board_signers = [
+        TransactionSigner {
+            pubkey: [0x0b; 32],
+            signed: 255,
+            rejected: 0,
+        },
+        TransactionSigner {
+            pubkey: engineering_vault.to_bytes(),
+            signed: 255,
+            rejected: 0,
+        },
+    ];
+
+    // Executing it from the engineering multisig would lend the vault as a
+    // signer beyond the executor.
+    let tx_accounts = [
+        TransactionAccount {
+            pubkey: engineering_vault.to_bytes(),
+            is_signer: 1,
+            is_writable: 1,
+        },
+        writable(&board_transaction),
+        TransactionAccount {
+            pubkey: board.to_bytes(),
+            is_signer: 0,
+            is_writable: 0,
+        },
+        TransactionAccount {
+            pubkey: system_program.to_bytes(),
+            is_signer: 0,
+            is_writable: 0,
+        },
+        TransactionAccount {
+            pubkey: engineering_vault.to_bytes(),
+            is_signer: 1,
+            is_writable: 1,
+        },
+        writable(&recipient),
+    ];
+    execute_as_engineering(
+        &[3],
+        &tx_accounts,
+        &[
+            (
+                engineering_vault,
+                Account::new(LAMPORTS_PER_SOL, 0, &system_program),
+            ),
+            (
+                board_transaction,
+                transaction_account(
+                    &board_transaction_header,
+                    &board_accounts,
+                    &board_signers,
+                    &data,
+                    2,
+                ),
+            ),
+            (board, board_account),
+            (system_program, system_account),
+            (recipient, Account::new(0, 0, &system_program)),
+        ],
+        &[Check::err(ProgramError::Custom(
+            CustomError::ProtectedAccount as u32,
+        ))],
+    );
+}
+
+/// Creates a transaction of a 2-of-2 multisig, proposed by owner 1, that
+/// invokes `program_id` with `tx_accounts` and `data`, built from the
+/// multisig and the transaction. `accounts` are the keyed accounts for the
+/// writable `tx_accounts` other than these two.
+fn create_transaction_invoking(
+    program_id: &Pubkey,
+    tx_accounts: impl FnOnce(&Pubkey, &Pubkey) -> Vec<TransactionAccount>,
+    data: &[u8],
+    accounts: &[(Pubkey, Account)],
+    checks: &[Check],
+) {
+    let mollusk = mollusk();
+
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+
+    let (multisig, multisig_header) = multisig_header(1, 2, 2);
+    let (transaction, mut transaction_header) =
+        transaction_header(1, &multisig, program_id, 0, 2, data.len() as u64);
+    let tx_accounts = tx_accounts(&multisig, &transaction);
+    transaction_header.num_accounts = u64::to_le_bytes(tx_accounts.len() as u64);
+    let tx_signers = [
+        TransactionSigner {
+            pubkey: owner_1.to_bytes(),
+            signed: 255,
+            rejected: 0,
+        },
+        TransactionSigner {
+            pubkey: owner_2.to_bytes(),
+            signed: 0,
+            rejected: 0,
+        },
+    ];
+
+    // instruction discriminator = 1
+    let mut ser_instruction_data = vec![1];
+    ser_instruction_data.extend_from_slice(bytemuck::bytes_of(&transaction_header));
+    ser_instruction_data
+        .extend_from_slice(bytemuck::cast_slice::<TransactionAccount, u8>(&tx_accounts));
+    ser_instruction_data
+        .extend_from_slice(bytemuck::cast_slice::<TransactionSigner, u8>(&tx_signers));
+    ser_instruction_data.extend_from_slice(data);
+
+    let mut account_metas = vec![
+        AccountMeta::new(owner_1, true),
+        AccountMeta::new(transaction, false),
+        AccountMeta::new(multisig, false),
+        AccountMeta::new_readonly(system_program, false),
+    ];
+    account_metas.extend(
+        tx_accounts
+            .iter()
+            .filter(|account| account.is_writable != 0)
+            .map(|account| AccountMeta::new(Pubkey::new_from_array(account.pubkey), false)),
+    );
+    let instruction = Instruction::new_with_bytes(PROGRAM, &ser_instruction_data, account_metas);
+
+    let mut keyed_accounts = vec![
+        (owner_1, Account::new(LAMPORTS_PER_SOL, 0, &system_program)),
+        (transaction, Account::new(0, 0, &system_program)),
+        (
+            multisig,
+            multisig_account(&multisig_header, &[owner_1, owner_2]),
+        ),
+        (system_program, system_account),
+    ];
+    keyed_accounts.extend_from_slice(accounts);
+
+    mollusk.process_and_validate_instruction(&instruction, &keyed_accounts, checks);
+}
+
+fn writable(pubkey: &Pubkey) -> TransactionAccount {
+    TransactionAccount {
+        pubkey: pubkey.to_bytes(),
+        is_signer: 0,
+        is_writable: 1,
+    }
+}
+
+#[test]
+fn test_create_transaction_protected_accounts() {
+    let (system_program, _) = mollusk_svm::program::keyed_account_for_system_program();
+
+    // The multisig, the transaction and the other transactions of the
+    // multisig cannot be handed to another program...
+    create_transaction_invoking(
+        &system_program,
+        |multisig, _| vec![writable(multisig)],
+        &[],
+        &[],
+        &[Check::err(ProgramError::Custom(
+            CustomError::ProtectedAccount as u32,
+        ))],
+    );
+    create_transaction_invoking(
+        &system_program,
+        |_, transaction| vec![writable(transaction)],
+        &[],
+        &[],
+        &[Check::err(ProgramError::Custom(
+            CustomError::ProtectedAccount as u32,
+        ))],
+    );
+
+    let (multisig, _) = multisig_header(1, 2, 2);
+    let (other, other_header) = transaction_header(2, &multisig, &system_program, 0, 2, 0);
+    let other_signers = [
+        TransactionSigner {
+            pubkey: [0x01; 32],
+            signed: 0,
+            rejected: 0,
+        },
+        TransactionSigner {
+            pubkey: [0x02; 32],
+            signed: 0,
+            rejected: 0,
+        },
+    ];
+    create_transaction_invoking(
+        &system_program,
+        |_, _| vec![writable(&other)],
+        &[],
+        &[(
+            other,
+            transaction_account(&other_header, &[], &other_signers, &[], 2),
+        )],
+        &[Check::err(ProgramError::Custom(
+            CustomError::ProtectedAccount as u32,
+        ))],
+    );
+
+    // ...nor to this program, except to change the configuration.
+    create_transaction_invoking(
+        &PROGRAM,
+        |multisig, _| vec![writable(multisig)],
+        &[4],
+        &[],
+        &[Check::err(ProgramError::Custom(
+            CustomError::ProtectedAccount as u32,
+        ))],
+    );
+    create_transaction_invoking(
+        &PROGRAM,
+        |multisig, _| vec![writable(multisig)],
+        &[13],
+        &[],
+        &[Check::success()],
+    );
+
+    // Only some instructions of this program can be invoked at all.
+    create_transaction_invoking(
+        &PROGRAM,
+        |_, _| vec![],
+        &[0],
+        &[],
+        &[Check::err(ProgramError::Custom(
+            CustomError::ProgramNotAllowed as u32,
+        ))],
+    );
+}
+
+#[test]
+fn test_execute_protected_accounts() {
+    let (engineering, _) = multisig_header(10, 1, 1);
+    let engineering_owner = Pubkey::new_from_array([0x0a; 32]);
+    let (engineering_vault, _) = vault(&engineering, 0);
+
+    // A transaction created before the checks cannot pause the multisig...
+    execute_as_engineering(
+        &[4],
+        &[writable(&engineering)],
+        &[],
+        &[Check::err(ProgramError::Custom(
+            CustomError::ProtectedAccount as u32,
+        ))],
+    );
+
+    // ...or write to another of its transactions.
+    let (other, other_header) = transaction_header(21, &engineering, &PROGRAM, 0, 1, 0);
+    let other_signers = [TransactionSigner {
+        pubkey: engineering_owner.to_bytes(),
+        signed: 0,
+        rejected: 0,
+    }];
+    execute_as_engineering(
+        &[2],
+        &[writable(&other)],
+        &[(
+            other,
+            transaction_account(&other_header, &[], &other_signers, &[], 1),
+        )],
+        &[Check::err(ProgramError::Custom(
+            CustomError::ProtectedAccount as u32,
+        ))],
+    );
+
+    // Nor can the vault be forwarded as a signer to another multisig, beside
+    // the executor or as the executor listed again.
+    let (board, board_header) = multisig_header(11, 1, 1);
+    let (board_transaction, _) = transaction_header(21, &board, &PROGRAM, 0, 1, 0);
+    let executor = TransactionAccount {
+        pubkey: engineering_vault.to_bytes(),
+        is_signer: 1,
+        is_writable: 0,
+    };
+    for forwarded in [
+        executor,
+        TransactionAccount {
+            is_signer: 0,
+            ..executor
+        },
+    ] {
+        execute_as_engineering(
+            &[3],
+            &[
+                executor,
+                writable(&board_transaction),
+                TransactionAccount {
+                    pubkey: board.to_bytes(),
+                    is_signer: 0,
+                    is_writable: 0,
+                },
+                TransactionAccount {
+                    pubkey: PROGRAM.to_bytes(),
+                    is_signer: 0,
+                    is_writable: 0,
+                },
+                forwarded,
+            ],
+            &[
+                (engineering_vault, Account::default()),
+                (board_transaction, Account::default()),
+                (board, multisig_account(&board_header, &[engineering_vault])),
+            ],
+            &[Check::err(ProgramError::Custom(
+                CustomError::ProtectedAccount as u32,
+            ))],
+        );
+    }
+
+    // Configuration changes go through.
+    let (system_program, _) = mollusk_svm::program::keyed_account_for_system_program();
+    let policy = threshold_policy(&system_program, &[], 1);
+    let mut data = vec![13];
+    data.extend_from_slice(bytemuck::bytes_of(&policy));
+    execute_as_engineering(
+        &data,
+        &[
+            writable(&engineering),
+            TransactionAccount {
+                pubkey: engineering_vault.to_bytes(),
+                is_signer: 1,
+                is_writable: 0,
+            },
+        ],
+        &[(engineering_vault, Account::new(0, 0, &system_program))],
+        &[
+            Check::success(),
+            Check::account(&engineering)
+                .data_slice(
+                    core::mem::offset_of!(MultisigHeader, threshold_policies),
+                    bytemuck::bytes_of(&policy),
+                )
+                .build(),
+        ],
+    );
+}
+
+/// Reports the compute units of the approve and execute path. Run with
+/// `make compute-units`.
+#[test]
+fn test_compute_units() {
+    let mollusk = mollusk();
+
+    let (system_program, system_account) = mollusk_svm::program::keyed_account_for_system_program();
+
+    let owner_1 = Pubkey::new_from_array([0x01; 32]);
+    let owner_2 = Pubkey::new_from_array([0x02; 32]);
+    let recipient = Pubkey::new_from_array([0x05; 32]);
+
+    let (multisig, multisig_account, transaction, transaction_account) =
+        approved_transfer(&recipient, 1_000, 0, |_| {});
+    let (vault, _) = vault(&multisig, 0);
+
+    // instruction discriminator = 2
+    let approve = Instruction::new_with_bytes(
+        PROGRAM,
+        &[2],
+        vec![
+            AccountMeta::new_readonly(owner_2, true),
+            AccountMeta::new(transaction, false),
+            AccountMeta::new_readonly(multisig, false),
+            AccountMeta::new_readonly(system_program, false),
+        ],
+    );
+    let result = mollusk.process_and_validate_instruction(
+        &approve,
+        &[
+            (owner_2, Account::default()),
+            (transaction, transaction_account),
+            (multisig, multisig_account.clone()),
+            (system_program, system_account.clone()),
+        ],
+        &[Check::success()],
+    );
+    println!("ApproveTransaction: {} CU", result.compute_units_consumed);
+    let transaction_account = result.get_account(&transaction).unwrap().clone();
+
+    // instruction discriminator = 3
+    let execute = Instruction::new_with_bytes(
+        PROGRAM,
+        &[3],
+        vec![
+            AccountMeta::new_readonly(owner_1, true),
+            AccountMeta::new(transaction, false),
+            AccountMeta::new_readonly(multisig, false),
+            AccountMeta::new_readonly(system_program, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(recipient, false),
+        ],
+    );
+    let result = mollusk.process_and_validate_instruction(
+        &execute,
+        &[
+            (owner_1, Account::default()),
+            (transaction, transaction_account),
+            (multisig, multisig_account),
+            (system_program, system_account),
+            (vault, Account::new(LAMPORTS_PER_SOL, 0, &system_program)),
+            (recipient, Account::new(0, 0, &system_program)),
+        ],
         &[Check::success()],
     );
+    println!("ExecuteTransaction: {} CU", result.compute_units_consumed);
 }